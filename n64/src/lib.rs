@@ -7,72 +7,333 @@ use rcp::*;
 mod pif;
 use pif::*;
 
+mod rdram;
+use rdram::*;
+
 mod mc;
 use mc::*;
 
+pub use mc::Device;
+
 pub const N64_ROM_HEADER_SIZE: usize = 0x40;
 
 /* N64 memory sizes. */
 pub const N64_IRAM_SIZE: usize = 0x400000;
+pub const N64_EXPANSION_IRAM_SIZE: usize = 0x800000;
+
+/* The RDRAM configurations the console can be fitted with. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Ram {
+    /* the 4MB of the base console */
+    Base,
+    /* 8MB with the Expansion Pak inserted */
+    Expansion
+}
+
+impl Ram {
+    /* Returns the amount of RDRAM installed in bytes. */
+    pub fn size(&self) -> usize {
+        match *self {
+            Ram::Base => N64_IRAM_SIZE,
+            Ram::Expansion => N64_EXPANSION_IRAM_SIZE
+        }
+    }
+}
+
+/* Steps between the reset button raising the pre-NMI interrupt and the NMI. Each step executes one
+   instruction, so at one instruction per cycle this is about half a second at 93.75 MHz. */
+const N64_NMI_DELAY: u64 = 46_875_000;
+
+/* The ways in which the console can be reset. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reset {
+    /* power cycle; everything but the cartridge starts over */
+    Cold,
+    /* the reset button; the PIF warns the game with a pre-NMI interrupt before the NMI */
+    Soft
+}
 
 pub struct N64 {
-    cart: Box<[u8]>,
-    rdram: Box<[u8]>,
     pub cpu: VR4300,
-    rcp: RCP,
-    pif: PIF
+    bus: Bus,
+
+    /* steps left until a pending NMI is delivered */
+    nmi: Option<u64>,
+
+    /* emulation options, kept across a power cycle */
+    caches: bool,
+    fixed_random: bool
 }
 
 impl N64 {
-    pub fn new(cart: Box<[u8]>, pifrom: Box<[u8]>) -> N64 {
+    pub fn new(cart: Box<[u8]>, pifrom: Box<[u8]>, ram: Ram) -> N64 {
         N64 {
-            cart: cart,
-            rdram: Box::new([0; N64_IRAM_SIZE]),
-            cpu: VR4300::new((PIF_ROM_START | KSEG0_START) as u64),
-            rcp: RCP::new(),
-            pif: PIF::new(pifrom)
+            cpu: VR4300::new(N64::boot_pc()),
+            bus: Bus::new(cart, pifrom, ram.size()),
+            nmi: None,
+
+            caches: false,
+            fixed_random: false
+        }
+    }
+
+    /* Enables the instruction and data cache models, at some cost in speed. */
+    pub fn set_caches(&mut self, enabled: bool) {
+        self.caches = enabled;
+        self.cpu.set_caches(enabled);
+    }
+
+    /* Makes TLBWR replacement independent of timing by only stepping Random on TLBWR, for deterministic replay. */
+    pub fn set_fixed_random(&mut self, fixed: bool) {
+        self.fixed_random = fixed;
+        self.cpu.cp0.set_fixed_random(fixed);
+    }
+
+    /* Attaches a custom device (a debug port, flashcart registers, ...) to a physical address range. */
+    pub fn attach(&mut self, start: u32, end: u32, dev: Box<dyn Device>) {
+        self.bus.attach(start, end, dev);
+    }
+
+    /* The CPU boots from the PIF ROM. */
+    fn boot_pc() -> u64 {
+        (PIF_ROM_START | KSEG0_START) as i32 as i64 as u64
+    }
+
+    /* Resets the console. */
+    pub fn reset(&mut self, kind: Reset) {
+        match kind {
+            Reset::Cold => {
+                let prom = std::mem::replace(&mut self.bus.pif.prom, Box::new([]));
+
+                /* the cartridge and any attached devices stay connected */
+                self.bus.rdram = Rdram::new(self.bus.rdram.mem.len());
+                self.bus.rcp = RCP::new();
+                self.bus.pif = PIF::new(prom);
+                self.cpu = VR4300::new(N64::boot_pc());
+                self.nmi = None;
+
+                /* the emulation options survive a power cycle */
+                self.cpu.set_caches(self.caches);
+                self.cpu.cp0.set_fixed_random(self.fixed_random);
+            }, Reset::Soft => {
+                /* pressing the button again while an NMI is pending has no effect */
+                if self.nmi.is_none() {
+                    self.nmi = Some(N64_NMI_DELAY);
+                }
+            }
         }
     }
 
     pub fn step(&mut self) {
-        let cart = &mut self.cart;
-        let rdram = &mut self.rdram;
-        let rcp = &mut self.rcp;
-        let pif = &mut self.pif;
+        /* the RCP's interrupt line is driven by the MI */
+        self.cpu.set_ip(INTR_RCP, self.bus.rcp.mi.pending());
 
-        let r = |addr| {
-            mc::read(addr, cart, rdram, rcp, pif)
-        };
+        /* the pre-NMI interrupt is held from the reset button until the NMI */
+        self.cpu.set_ip(INTR_PRENMI, self.nmi.is_some());
 
-        self.cpu.ic(r);
+        match self.nmi {
+            Some(0) => {
+                self.nmi = None;
+                self.cpu.set_ip(INTR_PRENMI, false);
+                self.cpu.nmi();
+            }, Some(n) => {
+                self.nmi = Some(n - 1);
+            }, None => {
+
+            }
+        }
+
+        self.cpu.ic(&self.bus);
         self.cpu.rf();
         self.cpu.ex();
-        self.cpu.dc(r);
+        self.cpu.dc(&self.bus);
+        self.cpu.wb(&mut self.bus);
+    }
+}
 
-        let w = |addr, val| {
-            mc::write(addr, val, rdram, rcp, pif)
-        };
+impl N64 {
+
+    /* Translates a virtual address for the debugger, failing if it is unmapped. */
+    fn vtop(&self, addr: u32, store: bool) -> Result<u32, String> {
+        match self.cpu.cp0.vtop(addr as i32 as i64 as u64, store) {
+            Ok((paddr, _)) => Ok(paddr),
+            Err(e) => Err(format!("{:#x} is untranslatable ({:?})", addr, e))
+        }
+    }
+
+    /* Reads a value of 1, 2, 4 or 8 bytes from a virtual address. */
+    fn vread(&self, addr: u32, size: usize) -> Result<u64, String> {
+        let paddr = self.vtop(addr, false)?;
+        self.bus.read(paddr, size).map_err(|_| format!("{:#x} (physical {:#x}) cannot be read", addr, paddr))
+    }
 
-        self.cpu.wb(w);
+    /* Writes a value of 1, 2, 4 or 8 bytes to a virtual address. */
+    fn vwrite(&mut self, addr: u32, val: u64, size: usize) -> Result<(), String> {
+        let paddr = self.vtop(addr, true)?;
+        self.bus.write(paddr, size, val).map_err(|_| format!("{:#x} (physical {:#x}) cannot be written", addr, paddr))
     }
 }
 
+/* Debugger access to memory through the CPU's address translation; unmapped addresses are reported as errors. */
 pub trait Read {
-    fn read(&self, addr: u32) -> u32;
+    fn read8(&self, addr: u32) -> Result<u8, String>;
+    fn read16(&self, addr: u32) -> Result<u16, String>;
+    fn read(&self, addr: u32) -> Result<u32, String>;
+    fn read64(&self, addr: u32) -> Result<u64, String>;
 }
 
 pub trait Write {
-    fn write(&mut self, addr: u32, val: u32);
+    fn write8(&mut self, addr: u32, val: u8) -> Result<(), String>;
+    fn write16(&mut self, addr: u32, val: u16) -> Result<(), String>;
+    fn write(&mut self, addr: u32, val: u32) -> Result<(), String>;
+    fn write64(&mut self, addr: u32, val: u64) -> Result<(), String>;
 }
 
 impl Read for N64 {
-    fn read(&self, addr: u32) -> u32 {
-        mc::read(addr, &self.cart, &self.rdram, &self.rcp, &self.pif)
+    fn read8(&self, addr: u32) -> Result<u8, String> {
+        self.vread(addr, 1).map(|v| v as u8)
+    }
+
+    fn read16(&self, addr: u32) -> Result<u16, String> {
+        self.vread(addr, 2).map(|v| v as u16)
+    }
+
+    fn read(&self, addr: u32) -> Result<u32, String> {
+        self.vread(addr, 4).map(|v| v as u32)
+    }
+
+    fn read64(&self, addr: u32) -> Result<u64, String> {
+        self.vread(addr, 8)
     }
 }
 
 impl Write for N64 {
-    fn write(&mut self, addr:u32, val: u32) {
-        mc::write(addr, val, &mut self.rdram, &mut self.rcp, &mut self.pif);
+    fn write8(&mut self, addr: u32, val: u8) -> Result<(), String> {
+        self.vwrite(addr, val as u64, 1)
+    }
+
+    fn write16(&mut self, addr: u32, val: u16) -> Result<(), String> {
+        self.vwrite(addr, val as u64, 2)
+    }
+
+    fn write(&mut self, addr: u32, val: u32) -> Result<(), String> {
+        self.vwrite(addr, val as u64, 4)
+    }
+
+    fn write64(&mut self, addr: u32, val: u64) -> Result<(), String> {
+        self.vwrite(addr, val, 8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Builds a console with a 4KB cartridge ROM. */
+    fn build() -> N64 {
+        build_ram(Ram::Base)
+    }
+
+    fn build_ram(ram: Ram) -> N64 {
+        N64::new(vec![0u8; 0x1000].into_boxed_slice(), vec![0u8; 0x7C0].into_boxed_slice(), ram)
+    }
+
+    /* Returns the uncached address of an RDRAM register of the modules answering to an ID. */
+    fn rdram_reg(id: u32, reg: u32) -> u32 {
+        0xA3F0_0000 | (id << 10) | reg
+    }
+
+    /* Returns a DeviceId value giving a module an ID. */
+    fn device_id(id: u32) -> u32 {
+        ((id & 0x3F) << 26) | ((id & 0x40) << 17) | (id & 0x180)
+    }
+
+    /* Sizes the RDRAM the way IPL3 does: every module is moved to a high ID, then the first module still
+       answering there is given the next free ID until none is left. Returns the amount found in bytes. */
+    fn size_rdram(n: &mut N64) -> usize {
+        const HIGH: u32 = 0x1F0;
+        let mut size = 0;
+
+        n.write(0xA3F8_0004, device_id(HIGH)).unwrap();
+        while n.read(rdram_reg(HIGH, 0x00)).unwrap() != 0 {
+            n.write(rdram_reg(HIGH, 0x04), device_id((size >> 20) as u32)).unwrap();
+            size += 0x200000;
+        }
+
+        size
+    }
+
+    #[test]
+    fn debugger_cart_access() {
+        let mut n = build();
+
+        assert_eq!(n.read(0xB000_0FFC), Ok(0));
+        assert!(n.read(0xB000_1000).is_err());
+        assert!(n.read64(0xB000_0FFC).is_err());
+        assert!(n.write(0xB000_0000, 1).is_err());
+    }
+
+    #[test]
+    fn debugger_bus_errors() {
+        let mut n = build();
+
+        /* nothing answers at physical 0x04900000, and the PIF ROM refuses writes */
+        assert!(n.read(0xA490_0000).is_err());
+        assert!(n.write(0xA490_0000, 1).is_err());
+        assert!(n.write(0xBFC0_0000, 1).is_err());
+
+        /* mapped kuseg addresses need a TLB entry */
+        assert!(n.read(0x0000_1000).is_err());
+    }
+
+    #[test]
+    fn rdram_sizing() {
+        let mut n = build_ram(Ram::Base);
+        assert_eq!(size_rdram(&mut n), N64_IRAM_SIZE);
+
+        let mut n = build_ram(Ram::Expansion);
+        assert_eq!(size_rdram(&mut n), N64_EXPANSION_IRAM_SIZE);
+
+        /* the Expansion Pak's modules hold data of their own */
+        n.write(0xA000_0000, 0x1111_1111).unwrap();
+        n.write(0xA040_0000, 0x4444_4444).unwrap();
+        n.write(0xA07F_FFFC, 0x7777_7777).unwrap();
+        assert_eq!(n.read(0xA000_0000), Ok(0x1111_1111));
+        assert_eq!(n.read(0xA040_0000), Ok(0x4444_4444));
+        assert_eq!(n.read(0xA07F_FFFC), Ok(0x7777_7777));
+        assert_eq!(&n.bus.rdram.mem[0x400000..0x400004], &[0x44; 4]);
+
+        /* nothing answers past the end */
+        assert_eq!(n.read(0xA080_0000), Ok(0));
+    }
+
+    #[test]
+    fn soft_reset() {
+        let mut n = build();
+        n.write(0xA000_0000, 0xDEAD_BEEF).unwrap();
+        for _ in 0..4 {
+            n.step();
+        }
+
+        /* the pre-NMI interrupt (IP4) is raised as soon as the button is pressed */
+        n.reset(Reset::Soft);
+        n.step();
+        assert_eq!(n.cpu.cp0.rgpr(13) & (1 << 12), 1 << 12);
+        assert_eq!(n.nmi, Some(N64_NMI_DELAY - 1));
+
+        /* the NMI restarts at the reset vector, whose instruction executes in the same step */
+        n.nmi = Some(0);
+        n.step();
+        assert_eq!(n.nmi, None);
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_0004);
+        assert_eq!(n.cpu.cp0.rgpr(30), 0xFFFF_FFFF_9FC0_0014);
+        assert_eq!(n.cpu.cp0.rgpr(13) & (1 << 12), 0);
+
+        /* Status.ERL, SR and BEV are set */
+        let status = n.cpu.cp0.rgpr(12);
+        assert_eq!(status & ((1 << 2) | (1 << 20) | (1 << 22)), (1 << 2) | (1 << 20) | (1 << 22));
+
+        /* RDRAM keeps its contents */
+        assert_eq!(n.read(0xA000_0000), Ok(0xDEAD_BEEF));
     }
 }
@@ -22,12 +22,13 @@
     0x8000 0000 to 0xFFFF FFFF External SysAD Device
 */
 
+use crate::Rdram;
 use crate::RCP;
 use crate::PIF;
 
 /* RDRAM memory. */
 pub const RDRAM_MEM_START:        u32 = 0x0000_0000;
-pub const RDRAM_MEM_END:        u32 = 0x003E_FFFF;
+pub const RDRAM_MEM_END:        u32 = 0x03EF_FFFF;
 
 /* RDRAM registers. */
 pub const RDRAM_REG_START:        u32 = 0x03F0_0000;
@@ -73,10 +74,6 @@ pub const RI_REG_END:            u32 = 0x047F_FFFF;
 pub const SI_REG_START:            u32 = 0x0480_0000;
 pub const SI_REG_END:            u32 = 0x048F_FFFF;
 
-/* Unused memory segment    . */
-pub const UNUSED_START:            u32 = 0x0490_0000;
-pub const UNUSED_END:            u32 = 0x04FF_FFFF;
-
 /* Cartridge memory. */
 pub const CART_DOM2_A1_START:    u32 = 0x0500_0000;
 pub const CART_DOM2_A1_END:        u32 = 0x05FF_FFFF;
@@ -95,159 +92,254 @@ pub const PIF_ROM_END:            u32 = 0x1FC0_07BF;
 pub const PIF_RAM_START:        u32 = 0x1FC0_07C0;
 pub const PIF_RAM_END:            u32 = 0x1FC0_07FF;
 
-/* Reserved memory space. */
-pub const RESERVED_START:        u32 = 0x1FC0_0800;
-pub const RESERVED_END:            u32 = 0x1FCF_FFFF;
-
-/* External device memory .*/
-pub const SYSAD_START:            u32 = 0x8000_0000;
-pub const SYSAD_END:            u32 = 0xFFFF_FFFF;
-
 /* Virtual memory spaces. */
 pub const KSEG0_START:            u32 = 0x8000_0000;
-pub const KSEG0_END:            u32 = 0x9FFF_FFFF;
-pub const KSEG1_START:            u32 = 0xA000_0000;
-pub const KSEG1_END:            u32 = 0xBFFF_FFFF;
 
-/* Reads a 32-bit word from a boxed slice of u8s. */
-fn rmem(addr: u32, mem: &[u8]) -> u32 {
+/* 64-bit virtual memory spaces; the 32-bit spaces appear sign-extended at the top. */
+pub const XUSEG_START:            u64 = 0x0000_0000_0000_0000;
+pub const XUSEG_END:            u64 = 0x0000_00FF_FFFF_FFFF;
+pub const XSSEG_START:            u64 = 0x4000_0000_0000_0000;
+pub const XSSEG_END:            u64 = 0x4000_00FF_FFFF_FFFF;
+pub const XKPHYS_START:            u64 = 0x8000_0000_0000_0000;
+pub const XKPHYS_END:            u64 = 0xBFFF_FFFF_FFFF_FFFF;
+pub const XKSEG_START:            u64 = 0xC000_0000_0000_0000;
+pub const XKSEG_END:            u64 = 0xC000_00FF_7FFF_FFFF;
+pub const CKSEG0_START:            u64 = 0xFFFF_FFFF_8000_0000;
+pub const CKSEG0_END:            u64 = 0xFFFF_FFFF_9FFF_FFFF;
+pub const CKSEG1_START:            u64 = 0xFFFF_FFFF_A000_0000;
+pub const CKSEG1_END:            u64 = 0xFFFF_FFFF_BFFF_FFFF;
+pub const CKSSEG_START:            u64 = 0xFFFF_FFFF_C000_0000;
+pub const CKSSEG_END:            u64 = 0xFFFF_FFFF_DFFF_FFFF;
+pub const CKSEG3_START:            u64 = 0xFFFF_FFFF_E000_0000;
+pub const CKSEG3_END:            u64 = 0xFFFF_FFFF_FFFF_FFFF;
+
+/* An access that no device answers, or that the device decoding it refuses. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BusError;
+
+/* Reads a big-endian value of 1, 2, 4 or 8 bytes from a boxed slice of u8s, failing past its end. */
+pub fn rmem(addr: u32, size: usize, mem: &[u8]) -> Result<u64, BusError> {
     /* Obtain a slice starting at the read address. */
-    let b: &[u8] = &mem[addr as usize .. addr as usize + 4];
-    /* Extract each of the word's bytes and use them to create a u32. */
-    let w = ((b[0] as u32) << 24) | ((b[1] as u32) << 16) | ((b[2] as u32) << 8) | b[3] as u32;
-    /* Byte swap and adjust the endianness of the read word. */
-    u32::from_be(w.swap_bytes())
+    let b: &[u8] = mem.get(addr as usize .. addr as usize + size).ok_or(BusError)?;
+    /* Combine the bytes, most significant first. */
+    Ok(b.iter().fold(0, |w, &b| (w << 8) | b as u64))
 }
 
-/* Writes a 32-bit word to a boxed slice of u8s. */
-fn wmem(addr: u32, val: u32, mem: &mut [u8]) {
-    /* Obtain a slice of bytes from the u32. */
-    let from: &[u8] = &[(val >> 24) as u8, (val >> 16) as u8, (val >> 8) as u8, val as u8];
+/* Writes a big-endian value of 1, 2, 4 or 8 bytes to a boxed slice of u8s, failing past its end. */
+pub fn wmem(addr: u32, size: usize, val: u64, mem: &mut [u8]) -> Result<(), BusError> {
+    /* Obtain the value's bytes, keeping the low-order ones for narrow writes. */
+    let from: &[u8] = &val.to_be_bytes()[8 - size ..];
     /* Write the slice into memory. */
-    mem[addr as usize .. addr as usize + 4].copy_from_slice(from)
+    mem.get_mut(addr as usize .. addr as usize + size).ok_or(BusError)?.copy_from_slice(from);
+    Ok(())
 }
 
-/* Convers a virtual address to a physical address. */
-fn vtop(vaddr: u32) -> u32 {
-    match vaddr {
-        KSEG0_START ..= KSEG0_END => {
-            /* Direct mapped segment KSEG0. */
-            vaddr - KSEG0_START
-        }, KSEG1_START ..= KSEG1_END => {
-            /* Direct mapped segment KSEG1. */
-            vaddr - KSEG1_START
-        }, _ => panic!("Unrecognized virtual address: address: {:#x}", vaddr)
+/* Returns the shift of a byte or halfword within the big-endian word that contains it. */
+fn lane(paddr: u32, size: usize) -> u32 {
+    (4 - size as u32 - (paddr & 3)) * 8
+}
+
+/* Reads from a register file that is only accessible in words. */
+pub fn rreg<F>(paddr: u32, size: usize, rreg: F) -> Result<u64, BusError> where F: Fn(u32) -> u32 {
+    Ok(match size {
+        8 => ((rreg(paddr) as u64) << 32) | rreg(paddr + 4) as u64,
+        4 => rreg(paddr) as u64,
+        _ => ((rreg(paddr & !3) >> lane(paddr, size)) as u64) & ((1 << (size * 8)) - 1)
+    })
+}
+
+/* Writes to a register file that is only accessible in words. */
+pub fn wreg<F>(paddr: u32, size: usize, val: u64, mut wreg: F) -> Result<(), BusError> where F: FnMut(u32, u32) {
+    match size {
+        8 => {
+            wreg(paddr, (val >> 32) as u32);
+            wreg(paddr + 4, val as u32);
+        }, 4 => {
+            wreg(paddr, val as u32)
+        }, _ => {
+            /* narrow writes place their data in its byte lanes of the word */
+            wreg(paddr & !3, (val as u32) << lane(paddr, size))
+        }
     }
+    Ok(())
+}
+
+/* A memory-mapped device on the bus. Devices are passed physical addresses and decode their own ranges. */
+pub trait Device {
+    /* Reads a value of 1, 2, 4 or 8 bytes from the device; failing raises a bus error on the CPU. */
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError>;
+    /* Writes a value of 1, 2, 4 or 8 bytes to the device; failing raises a bus error on the CPU. */
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError>;
 }
 
-/* Reads a word from the memory map. */
-pub fn read(addr: u32, cart: &[u8], rdram: &[u8], rcp: &RCP, pif: &PIF) -> u32 {
-
-    /* Convert the virtual address to a physical address. */
-    let paddr = vtop(addr);
-
-    /* Match the memory address to a peripheral address range. */
-    match paddr {
-        RDRAM_MEM_START ..= RDRAM_MEM_END => {
-            return rmem(paddr - RDRAM_MEM_START, rdram);
-        }, RDRAM_REG_START ..= RDRAM_REG_END => {
-            unimplemented!()
-        }, SP_DMEM_START ..= SP_DMEM_END => {
-            rmem(paddr - SP_DMEM_START, &rcp.rsp.dmem)
-        }, SP_IMEM_START ..= SP_IMEM_END => {
-            rmem(paddr - SP_IMEM_START, &rcp.rsp.imem)
-        }, SP_REG_START ..= SP_REG_END => {
-            rcp.rsp.rreg(paddr)
-        }, RDP_CMD_START ..= RDP_CMD_END => {
-            rcp.rdp.dpc_rreg(paddr)
-        }, RDP_SPAN_START ..= RDP_SPAN_END => {
-            rcp.rdp.dps_rreg(paddr)
-        }, MI_REG_START ..= MI_REG_END => {
-            rcp.mi.rreg(paddr)
-        }, VI_REG_START ..= VI_REG_END => {
-            rcp.vi.rreg(paddr)
-        }, AI_REG_START ..= AI_REG_END => {
-            rcp.ai.rreg(paddr)
-        }, PI_REG_START ..= PI_REG_END => {
-            rcp.pi.rreg(paddr)
-        }, RI_REG_START ..= RI_REG_END => {
-            rcp.ri.rreg(paddr)
-        }, SI_REG_START ..= SI_REG_END => {
-            pif.rreg(paddr)
-        }, UNUSED_START ..= UNUSED_END => {
-            panic!("Attempt to read from unused address space.")
-        }, CART_DOM2_A1_START ..= CART_DOM2_A1_END => {
-            rmem(paddr - CART_DOM2_A1_START, &cart)
-        }, CART_DOM1_A1_START ..= CART_DOM1_A1_END => {
-            rmem(paddr - CART_DOM1_A1_START, &cart)
-        }, CART_DOM2_A2_START ..= CART_DOM2_A2_END => {
-            rmem(paddr - CART_DOM2_A2_START, &cart)
-        }, CART_DOM1_A2_START ..= CART_DOM1_A2_END => {
-            rmem(paddr - CART_DOM1_A2_START, &cart)
-        }, PIF_ROM_START ..= PIF_ROM_END => {
-            rmem(paddr - PIF_ROM_START, &pif.prom)
-        }, PIF_RAM_START ..= PIF_RAM_END => {
-            rmem(paddr - PIF_RAM_START, &pif.pram)
-        }, RESERVED_START ..= RESERVED_END => {
-            panic!("Attempt to read from a reserved location address: {:#x}.", paddr)
-        }, CART_DOM1_A3_START ..= CART_DOM1_A3_END => {
-            rmem(paddr - CART_DOM1_A3_START, &cart)
-        }, SYSAD_START ..= SYSAD_END => {
-            unimplemented!()
-        }, _ => panic!("Read from unrecognized physical address: {:#x}", paddr)
+/* The cartridge ROM, which appears in every cartridge domain. Accesses past the end of the ROM and writes fail. */
+pub struct Cart {
+    pub rom: Box<[u8]>
+}
+
+impl Device for Cart {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        let start = match paddr {
+            CART_DOM2_A1_START ..= CART_DOM2_A1_END => CART_DOM2_A1_START,
+            CART_DOM1_A1_START ..= CART_DOM1_A1_END => CART_DOM1_A1_START,
+            CART_DOM2_A2_START ..= CART_DOM2_A2_END => CART_DOM2_A2_START,
+            CART_DOM1_A2_START ..= CART_DOM1_A2_END => CART_DOM1_A2_START,
+            _ => CART_DOM1_A3_START
+        };
+        rmem(paddr - start, size, &self.rom)
     }
+
+    fn write(&mut self, _: u32, _: usize, _: u64) -> Result<(), BusError> {
+        Err(BusError)
+    }
+}
+
+/* The devices the bus dispatches to. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Slot {
+    Rdram,
+    Rsp,
+    Rdp,
+    Mi,
+    Vi,
+    Ai,
+    Pi,
+    Ri,
+    Pif,
+    Cart,
+    /* a device attached by the user (index into the custom devices) */
+    Custom(usize)
+}
+
+/* A physical address range decoded by a device. */
+#[derive(Copy, Clone, Debug)]
+struct Mapping {
+    start: u32,
+    end: u32,
+    slot: Slot
+}
+
+/* The dispatch table divides the physical address space into 1MB pages. */
+const PAGE_SHIFT: u32 = 20;
+const PAGE_COUNT: usize = 1 << (32 - PAGE_SHIFT);
+
+/* The system bus, connecting the CPU to memory and the memory-mapped devices. */
+pub struct Bus {
+    pub rdram: Rdram,
+    pub cart: Cart,
+    pub rcp: RCP,
+    pub pif: PIF,
+
+    /* devices attached by the user */
+    custom: Vec<Box<dyn Device>>,
+
+    /* the mappings overlapping each page of the physical address space */
+    pages: Vec<Vec<Mapping>>
 }
 
-/* Writes a word to the provided N64's memory map. */
-pub fn write(addr: u32, value: u32, rdram: &mut [u8], rcp: &mut RCP, pif: &mut PIF) {
-
-    /* Convert the virtual address to the physical address. */
-    let paddr = vtop(addr);
-
-    /* Match the memory address to a peripheral address range. */
-    match paddr {
-        RDRAM_MEM_START ..= RDRAM_MEM_END => {
-            wmem(paddr - RDRAM_MEM_START, value, rdram)
-        }, RDRAM_REG_START ..= RDRAM_REG_END => {
-            unimplemented!()
-        }, SP_DMEM_START ..= SP_DMEM_END => {
-            wmem(paddr - SP_DMEM_START, value, &mut rcp.rsp.dmem)
-        }, SP_IMEM_START ..= SP_IMEM_END => {
-            wmem(paddr - SP_IMEM_START, value, &mut rcp.rsp.imem)
-        }, SP_REG_START ..= SP_REG_END => {
-            rcp.rsp.wreg(paddr, value)
-        }, RDP_CMD_START ..= RDP_CMD_END => {
-            rcp.rdp.dpc_wreg(paddr, value)
-        }, RDP_SPAN_START ..= RDP_SPAN_END => {
-            rcp.rdp.dps_wreg(paddr, value)
-        }, MI_REG_START ..= MI_REG_END => {
-            rcp.mi.wreg(paddr, value)
-        }, VI_REG_START ..= VI_REG_END => {
-            rcp.vi.wreg(paddr, value)
-        }, AI_REG_START ..= AI_REG_END => {
-            rcp.ai.wreg(paddr, value)
-        }, PI_REG_START ..= PI_REG_END => {
-            rcp.pi.wreg(paddr, value)
-        }, RI_REG_START ..= RI_REG_END => {
-            rcp.ri.wreg(paddr, value)
-        }, SI_REG_START ..= SI_REG_END => {
-            pif.wreg(paddr, value)
-        }, UNUSED_START ..= UNUSED_END => {
-            panic!("Attempt to write to unused address space.")
-        }, CART_DOM2_A1_START ..= CART_DOM2_A1_END |
-           CART_DOM1_A1_START ..= CART_DOM1_A1_END |
-           CART_DOM2_A2_START ..= CART_DOM2_A2_END |
-           CART_DOM1_A2_START ..= CART_DOM1_A2_END |
-           CART_DOM1_A3_START ..= CART_DOM1_A3_END => {
-            panic!("Attempt to write to read-only cartridge memory address: {:#x}.", paddr)
-        }, PIF_ROM_START ..= PIF_ROM_END => {
-            panic!("Attempt to write to a read-only PIF memory address: {:#x}.", paddr)
-        }, PIF_RAM_START ..= PIF_RAM_END => {
-            wmem(paddr - PIF_RAM_START, value, &mut pif.pram)
-        }, RESERVED_START ..= RESERVED_END => {
-            panic!("Attempt to write to a reserved location address: {:#x}.", paddr)
-        }, SYSAD_START ..= SYSAD_END => {
-            unimplemented!()
-        }, _ => panic!("Write to unrecognized physical address: address: {:#x}", paddr)
+impl Bus {
+    pub fn new(cart: Box<[u8]>, pifrom: Box<[u8]>, ram: usize) -> Bus {
+        let mut bus = Bus {
+            rdram: Rdram::new(ram),
+            cart: Cart { rom: cart },
+            rcp: RCP::new(),
+            pif: PIF::new(pifrom),
+
+            custom: Vec::new(),
+            pages: vec![Vec::new(); PAGE_COUNT]
+        };
+
+        bus.map(RDRAM_MEM_START, RDRAM_MEM_END, Slot::Rdram);
+        bus.map(RDRAM_REG_START, RDRAM_REG_END, Slot::Rdram);
+        bus.map(SP_DMEM_START, SP_DMEM_END, Slot::Rsp);
+        bus.map(SP_IMEM_START, SP_IMEM_END, Slot::Rsp);
+        bus.map(SP_REG_START, SP_REG_END, Slot::Rsp);
+        bus.map(RDP_CMD_START, RDP_CMD_END, Slot::Rdp);
+        bus.map(RDP_SPAN_START, RDP_SPAN_END, Slot::Rdp);
+        bus.map(MI_REG_START, MI_REG_END, Slot::Mi);
+        bus.map(VI_REG_START, VI_REG_END, Slot::Vi);
+        bus.map(AI_REG_START, AI_REG_END, Slot::Ai);
+        bus.map(PI_REG_START, PI_REG_END, Slot::Pi);
+        bus.map(RI_REG_START, RI_REG_END, Slot::Ri);
+        bus.map(SI_REG_START, SI_REG_END, Slot::Pif);
+        bus.map(CART_DOM2_A1_START, CART_DOM2_A1_END, Slot::Cart);
+        bus.map(CART_DOM1_A1_START, CART_DOM1_A1_END, Slot::Cart);
+        bus.map(CART_DOM2_A2_START, CART_DOM2_A2_END, Slot::Cart);
+        bus.map(CART_DOM1_A2_START, CART_DOM1_A2_END, Slot::Cart);
+        bus.map(PIF_ROM_START, PIF_RAM_END, Slot::Pif);
+        bus.map(CART_DOM1_A3_START, CART_DOM1_A3_END, Slot::Cart);
+
+        bus
+    }
+
+    /* Registers a device for a physical address range; later mappings take precedence. */
+    fn map(&mut self, start: u32, end: u32, slot: Slot) {
+        let m = Mapping { start, end, slot };
+
+        for page in (start >> PAGE_SHIFT) ..= (end >> PAGE_SHIFT) {
+            self.pages[page as usize].insert(0, m);
+        }
+    }
+
+    /* Attaches a custom device to a physical address range, in front of any device already there. */
+    pub fn attach(&mut self, start: u32, end: u32, dev: Box<dyn Device>) {
+        self.custom.push(dev);
+        let slot = Slot::Custom(self.custom.len() - 1);
+        self.map(start, end, slot);
+    }
+
+    /* Finds the device decoding a physical address. */
+    fn lookup(&self, paddr: u32) -> Option<Slot> {
+        self.pages[(paddr >> PAGE_SHIFT) as usize].iter()
+            .find(|m| m.start <= paddr && paddr <= m.end)
+            .map(|m| m.slot)
+    }
+
+    fn device(&self, slot: Slot) -> &dyn Device {
+        match slot {
+            Slot::Rdram => &self.rdram,
+            Slot::Rsp => &self.rcp.rsp,
+            Slot::Rdp => &self.rcp.rdp,
+            Slot::Mi => &self.rcp.mi,
+            Slot::Vi => &self.rcp.vi,
+            Slot::Ai => &self.rcp.ai,
+            Slot::Pi => &self.rcp.pi,
+            Slot::Ri => &self.rcp.ri,
+            Slot::Pif => &self.pif,
+            Slot::Cart => &self.cart,
+            Slot::Custom(i) => &*self.custom[i]
+        }
+    }
+
+    fn device_mut(&mut self, slot: Slot) -> &mut dyn Device {
+        match slot {
+            Slot::Rdram => &mut self.rdram,
+            Slot::Rsp => &mut self.rcp.rsp,
+            Slot::Rdp => &mut self.rcp.rdp,
+            Slot::Mi => &mut self.rcp.mi,
+            Slot::Vi => &mut self.rcp.vi,
+            Slot::Ai => &mut self.rcp.ai,
+            Slot::Pi => &mut self.rcp.pi,
+            Slot::Ri => &mut self.rcp.ri,
+            Slot::Pif => &mut self.pif,
+            Slot::Cart => &mut self.cart,
+            Slot::Custom(i) => &mut *self.custom[i]
+        }
+    }
+
+    /* Reads a value of 1, 2, 4 or 8 bytes from the physical address space. */
+    pub fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        match self.lookup(paddr) {
+            Some(slot) => self.device(slot).read(paddr, size),
+            None => Err(BusError)
+        }
+    }
+
+    /* Writes a value of 1, 2, 4 or 8 bytes to the physical address space. */
+    pub fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        match self.lookup(paddr) {
+            Some(slot) => self.device_mut(slot).write(paddr, size, val),
+            None => Err(BusError)
+        }
     }
 }
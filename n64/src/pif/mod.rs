@@ -1,5 +1,13 @@
+use crate::mc::PIF_ROM_START;
+use crate::mc::PIF_ROM_END;
 use crate::mc::PIF_RAM_START;
 use crate::mc::PIF_RAM_END;
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rmem;
+use crate::mc::wmem;
+use crate::mc::rreg;
+use crate::mc::wreg;
 
 const SI_REG_DRAM_ADDR: u32 = 0x0480_0000;
 const SI_REG_PIF_ADDR_RD64B: u32 = 0x0480_0004;
@@ -65,3 +73,22 @@ impl PIF {
         }
     }
 }
+
+/* The PIF decodes its boot ROM and RAM, as well as the SI registers in front of it. */
+impl Device for PIF {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        match paddr {
+            PIF_ROM_START ..= PIF_ROM_END => rmem(paddr - PIF_ROM_START, size, &self.prom),
+            PIF_RAM_START ..= PIF_RAM_END => rmem(paddr - PIF_RAM_START, size, &self.pram),
+            _ => rreg(paddr, size, |a| self.rreg(a))
+        }
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        match paddr {
+            PIF_ROM_START ..= PIF_ROM_END => Err(BusError),
+            PIF_RAM_START ..= PIF_RAM_END => wmem(paddr - PIF_RAM_START, size, val, &mut self.pram),
+            _ => wreg(paddr, size, val, |a, v| self.wreg(a, v))
+        }
+    }
+}
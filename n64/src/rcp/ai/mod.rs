@@ -1,3 +1,8 @@
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const AI_REG_DRAM_ADDR: u32 = 0x0450_0000;
 const AI_REG_LEN: u32 = 0x0450_0004;
 const AI_REG_CONTROL: u32 = 0x0450_0008;
@@ -98,3 +103,13 @@ impl AI {
         }
     }
 }
+
+impl Device for AI {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        rreg(paddr, size, |a| self.rreg(a))
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        wreg(paddr, size, val, |a, v| self.wreg(a, v))
+    }
+}
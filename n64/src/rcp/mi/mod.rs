@@ -1,3 +1,8 @@
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const MI_REG_INIT_MODE: u32 = 0x0430_0000;
 const MI_REG_VERSION: u32 = 0x0430_0004;
 const MI_REG_INTR: u32 = 0x0430_0008;
@@ -20,6 +25,11 @@ impl MI {
         }
     }
 
+    /* Determines whether any unmasked RCP interrupt is pending. */
+    pub fn pending(&self) -> bool {
+        (self.intr & self.intr_mask) != 0
+    }
+
     /* Reads from the MI's registers. */
     pub fn rreg(&self, reg: u32) -> u32 {
         match reg {
@@ -50,3 +60,13 @@ impl MI {
         }
     }
 }
+
+impl Device for MI {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        rreg(paddr, size, |a| self.rreg(a))
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        wreg(paddr, size, val, |a, v| self.wreg(a, v))
+    }
+}
@@ -1,3 +1,8 @@
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const PI_REG_DRAM_ADDR: u32 = 0x0460_0000;
 const PI_REG_CART_ADDR: u32 = 0x0460_0004;
 const PI_REG_RD_LEN: u32 = 0x0460_0008;
@@ -162,3 +167,13 @@ impl PI {
         }
     }
 }
+
+impl Device for PI {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        rreg(paddr, size, |a| self.rreg(a))
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        wreg(paddr, size, val, |a, v| self.wreg(a, v))
+    }
+}
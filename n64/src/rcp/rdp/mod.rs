@@ -1,3 +1,10 @@
+use crate::mc::RDP_CMD_START;
+use crate::mc::RDP_CMD_END;
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const DPC_REG_START: u32 = 0x0410_0000;
 const DPC_REG_END: u32 = 0x0410_0004;
 const DPC_REG_CURRENT: u32 = 0x0410_0008;
@@ -123,3 +130,19 @@ impl RDP {
         }
     }
 }
+
+impl Device for RDP {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        match paddr {
+            RDP_CMD_START ..= RDP_CMD_END => rreg(paddr, size, |a| self.dpc_rreg(a)),
+            _ => rreg(paddr, size, |a| self.dps_rreg(a))
+        }
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        match paddr {
+            RDP_CMD_START ..= RDP_CMD_END => wreg(paddr, size, val, |a, v| self.dpc_wreg(a, v)),
+            _ => wreg(paddr, size, val, |a, v| self.dps_wreg(a, v))
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const RI_REG_MODE: u32 = 0x0470_0000;
 const RI_REG_CONFIG: u32 = 0x0470_0004;
 const RI_REG_CURRENT_LOAD: u32 = 0x0470_0008;
@@ -7,15 +12,63 @@ const RI_REG_LATENCY: u32 = 0x0470_0014;
 const RI_REG_RERROR: u32 = 0x0470_0018;
 const RI_REG_WERROR: u32 = 0x0470_001C;
 
+/*
+
+RI_BASE_REG - 0x04700000
+
+        0x04700000 to 0x04700003  RI_MODE_REG //RI mode
+           (RW): [1:0] operating mode
+                 [2] stop T active
+                 [3] stop R active
+
+        0x04700004 to 0x04700007  RI_CONFIG_REG //RI config
+           (RW): [5:0] current control input
+                 [6] current control enable
+
+        0x04700008 to 0x0470000B  RI_CURRENT_LOAD_REG //RI current load
+           (W): [] any write updates current control register
+
+        0x0470000C to 0x0470000F  RI_SELECT_REG //RI select
+           (RW): [2:0] receive select
+                 [6:4] transmit select
+
+        0x04700010 to 0x04700013  RI_REFRESH_REG or RI_COUNT_REG //RI refresh
+           (RW): [7:0] clean refresh delay
+                 [15:8] dirty refresh delay
+                 [16] refresh bank
+                 [17] refresh enable
+                 [18] refresh optimize
+                 [19] refresh multibank
+
+        0x04700014 to 0x04700017  RI_LATENCY_REG //RI latency
+           (RW): [3:0] DMA latency/overlap
+
+        0x04700018 to 0x0470001B  RI_RERROR_REG //RI error
+           (R): [0] nack error
+                [1] ack error
+
+        0x0470001C to 0x0470001F  RI_WERROR_REG //RI error
+           (W): [] any write clears all error bits
+
+        The RI's settings only tune the electrical interface to the RDRAM modules and are
+        stored for the boot code to read back; they have no effect on emulated timing.
+
+*/
+
+/* Bits of the registers that hold state. */
+const RI_MODE_MASK: u32 = 0xF;
+const RI_CONFIG_MASK: u32 = 0x7F;
+const RI_SELECT_MASK: u32 = 0x77;
+const RI_REFRESH_MASK: u32 = 0xF_FFFF;
+const RI_LATENCY_MASK: u32 = 0xF;
+
 pub struct RI {
     mode: u32,
     config: u32,
-    current_load: u32,
     select: u32,
     refresh: u32,
     latency: u32,
-    rerror: u32,
-    werror: u32
+    rerror: u32
 }
 
 impl RI {
@@ -23,12 +76,10 @@ impl RI {
         RI {
             mode: 0,
             config: 0,
-            current_load: 0,
             select: 0,
             refresh: 0,
             latency: 0,
-            rerror: 0,
-            werror: 0
+            rerror: 0
         }
     }
 
@@ -40,7 +91,7 @@ impl RI {
             }, RI_REG_CONFIG => {
                 self.config
             }, RI_REG_CURRENT_LOAD => {
-                self.current_load
+                0
             }, RI_REG_SELECT => {
                 self.select
             }, RI_REG_REFRESH => {
@@ -50,7 +101,7 @@ impl RI {
             }, RI_REG_RERROR => {
                 self.rerror
             }, RI_REG_WERROR => {
-                self.werror
+                0
             }, _ => panic!("Read from unrecognized RI register address: {:#x}", reg)
         }
     }
@@ -59,22 +110,32 @@ impl RI {
     pub fn wreg(&mut self, reg: u32, value: u32) {
         match reg {
             RI_REG_MODE => {
-                self.mode = value
+                self.mode = value & RI_MODE_MASK
             }, RI_REG_CONFIG => {
-                self.config = value
+                self.config = value & RI_CONFIG_MASK
             }, RI_REG_CURRENT_LOAD => {
-                self.current_load = value
+                /* the current control calibration completes immediately */
             }, RI_REG_SELECT => {
-                self.select = value
+                self.select = value & RI_SELECT_MASK
             }, RI_REG_REFRESH => {
-                self.refresh = value
+                self.refresh = value & RI_REFRESH_MASK
             }, RI_REG_LATENCY => {
-                self.latency = value
+                self.latency = value & RI_LATENCY_MASK
             }, RI_REG_RERROR => {
-                self.rerror = value
+
             }, RI_REG_WERROR => {
-                self.werror = value
+                self.rerror = 0
             }, _ => panic!("Write to unrecognized RI register address: {:#x}", reg)
         }
     }
 }
+
+impl Device for RI {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        rreg(paddr, size, |a| self.rreg(a))
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        wreg(paddr, size, val, |a, v| self.wreg(a, v))
+    }
+}
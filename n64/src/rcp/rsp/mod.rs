@@ -2,6 +2,12 @@ use crate::mc::SP_DMEM_START;
 use crate::mc::SP_DMEM_END;
 use crate::mc::SP_IMEM_START;
 use crate::mc::SP_IMEM_END;
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rmem;
+use crate::mc::wmem;
+use crate::mc::rreg;
+use crate::mc::wreg;
 
 /* LO registers */
 const SP_REG_MEM_ADDR: u32 = 0x0404_0000;
@@ -199,3 +205,21 @@ impl RSP {
         }
     }
 }
+
+impl Device for RSP {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        match paddr {
+            SP_DMEM_START ..= SP_DMEM_END => rmem(paddr - SP_DMEM_START, size, &self.dmem),
+            SP_IMEM_START ..= SP_IMEM_END => rmem(paddr - SP_IMEM_START, size, &self.imem),
+            _ => rreg(paddr, size, |a| self.rreg(a))
+        }
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        match paddr {
+            SP_DMEM_START ..= SP_DMEM_END => wmem(paddr - SP_DMEM_START, size, val, &mut self.dmem),
+            SP_IMEM_START ..= SP_IMEM_END => wmem(paddr - SP_IMEM_START, size, val, &mut self.imem),
+            _ => wreg(paddr, size, val, |a, v| self.wreg(a, v))
+        }
+    }
+}
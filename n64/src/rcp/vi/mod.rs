@@ -1,3 +1,8 @@
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
 const VI_REG_STATUS: u32 = 0x0440_0000;
 const VI_REG_ORIGIN: u32 = 0x0440_0004;
 const VI_REG_WIDTH: u32 = 0x0440_0008;
@@ -202,3 +207,13 @@ impl VI {
         }
     }
 }
+
+impl Device for VI {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        rreg(paddr, size, |a| self.rreg(a))
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        wreg(paddr, size, val, |a, v| self.wreg(a, v))
+    }
+}
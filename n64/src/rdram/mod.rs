@@ -0,0 +1,353 @@
+use crate::mc::RDRAM_MEM_START;
+use crate::mc::RDRAM_REG_START;
+use crate::mc::RDRAM_REG_END;
+use crate::mc::Device;
+use crate::mc::BusError;
+use crate::mc::rmem;
+use crate::mc::wmem;
+use crate::mc::rreg;
+use crate::mc::wreg;
+
+/* Register offsets within a module's register file. */
+const RDRAM_REG_DEVICE_TYPE: u32 = 0x00;
+const RDRAM_REG_DEVICE_ID: u32 = 0x04;
+const RDRAM_REG_DELAY: u32 = 0x08;
+const RDRAM_REG_MODE: u32 = 0x0C;
+const RDRAM_REG_REF_INTERVAL: u32 = 0x10;
+const RDRAM_REG_REF_ROW: u32 = 0x14;
+const RDRAM_REG_RAS_INTERVAL: u32 = 0x18;
+const RDRAM_REG_MIN_INTERVAL: u32 = 0x1C;
+const RDRAM_REG_ADDR_SELECT: u32 = 0x20;
+const RDRAM_REG_DEVICE_MANUF: u32 = 0x24;
+
+/*
+
+RDRAM_BASE_REG - 0x03F00000
+
+        0x03F00000 to 0x03F7FFFF  Individual module registers
+           Bits [18:10] of the address select the module whose DeviceId matches; the
+           modules form a daisy chain, so when several share an ID only the first responds.
+
+        0x03F80000 to 0x03FFFFFF  Broadcast registers
+           (W): written to every module. Reads return zero.
+
+        Each module decodes the low 10 bits of the address as:
+
+        0x00  RDRAM_DEVICE_TYPE_REG   (R): module type, version and geometry
+        0x04  RDRAM_DEVICE_ID_REG     (RW): [31:26] IdField[5:0], [23] IdField[6], [8:7] IdField[8:7]
+        0x08  RDRAM_DELAY_REG         (RW): ack/read/write delays
+        0x0C  RDRAM_MODE_REG          (RW): [31:30], [23:22], [15:14], [7:6] read back inverted (current control)
+        0x10  RDRAM_REF_INTERVAL_REG  (RW): refresh interval
+        0x14  RDRAM_REF_ROW_REG       (RW): next row to refresh
+        0x18  RDRAM_RAS_INTERVAL_REG  (RW): RAS timing
+        0x1C  RDRAM_MIN_INTERVAL_REG  (RW): minimum intervals
+        0x20  RDRAM_ADDR_SELECT_REG   (RW): swap field, laid out as IdField; the row bits it sets are
+                                            inverted before a data access is compared with the ID
+        0x24  RDRAM_DEVICE_MANUF_REG  (R): manufacturer
+
+RDRAM_BASE - 0x00000000
+
+        A module answers the data accesses whose address bits [28:20] select its ID, each module
+        spanning as many IDs as it holds megabytes. Until the boot code assigns IDs, the modules
+        keep IDs laying them out one after another from address zero, so that code running before
+        IPL3 (and emulated code that skips it) sees all of the memory. Accesses no module answers
+        read as zero.
+
+*/
+
+/* Every module holds 2MB. */
+const RDRAM_MODULE_SIZE: usize = 0x200000;
+
+/* Address bit that selects the broadcast registers, and the bits selecting a module. */
+const RDRAM_REG_BROADCAST: u32 = 1 << 19;
+const RDRAM_REG_ID_SHIFT: u32 = 10;
+const RDRAM_REG_ID_MASK: u32 = 0x1FF;
+
+/* Data address bits compared with the module IDs. */
+const RDRAM_MEM_ID_SHIFT: u32 = 20;
+const RDRAM_MEM_ID_MASK: u32 = 0x1FF;
+
+/* Bits of the mode register that read back inverted. */
+const RDRAM_MODE_INVERTED: u32 = 0xC0C0_C0C0;
+
+/* Gathers a 9-bit ID from its fields in DeviceId and AddressSelect. */
+fn idfield(reg: u32) -> u32 {
+    ((reg >> 26) & 0x3F) | ((reg >> 17) & 0x40) | (reg & 0x180)
+}
+
+/* Scatters a 9-bit ID into its fields in DeviceId and AddressSelect. */
+fn idreg(id: u32) -> u32 {
+    ((id & 0x3F) << 26) | ((id & 0x40) << 17) | (id & 0x180)
+}
+
+/* A module's register file. */
+#[derive(Copy, Clone, Debug)]
+struct Module {
+    device_type: u32,
+    device_id: u32,
+    delay: u32,
+    mode: u32,
+    ref_interval: u32,
+    ref_row: u32,
+    ras_interval: u32,
+    min_interval: u32,
+    addr_select: u32,
+    device_manuf: u32
+}
+
+impl Module {
+    /* Creates the module at a position in the daisy chain, mapped after the modules before it. */
+    fn new(index: usize) -> Module {
+        let span = RDRAM_MODULE_SIZE >> RDRAM_MEM_ID_SHIFT;
+
+        Module {
+            device_type: 0xB419_0010,
+            device_id: idreg((index * span) as u32),
+            delay: 0x230B_0223,
+            mode: 0xC4C0_C0C0,
+            ref_interval: 0,
+            ref_row: 0,
+            ras_interval: 0,
+            min_interval: 0x0040_C0E0,
+            addr_select: 0,
+            device_manuf: 0x0000_0500
+        }
+    }
+
+    /* Returns the ID the module answers to, gathered from the fields of DeviceId. */
+    fn id(&self) -> u32 {
+        idfield(self.device_id)
+    }
+
+    /* Determines whether the module answers a data access to the given row (address bits [28:20]). */
+    fn selects(&self, row: u32) -> bool {
+        let span = (RDRAM_MODULE_SIZE >> RDRAM_MEM_ID_SHIFT) as u32;
+        ((row ^ idfield(self.addr_select)) / span) == (self.id() / span)
+    }
+
+    /* Reads from the module's registers. */
+    fn rreg(&self, reg: u32) -> u32 {
+        match reg {
+            RDRAM_REG_DEVICE_TYPE => {
+                self.device_type
+            }, RDRAM_REG_DEVICE_ID => {
+                self.device_id
+            }, RDRAM_REG_DELAY => {
+                self.delay
+            }, RDRAM_REG_MODE => {
+                self.mode ^ RDRAM_MODE_INVERTED
+            }, RDRAM_REG_REF_INTERVAL => {
+                self.ref_interval
+            }, RDRAM_REG_REF_ROW => {
+                self.ref_row
+            }, RDRAM_REG_RAS_INTERVAL => {
+                self.ras_interval
+            }, RDRAM_REG_MIN_INTERVAL => {
+                self.min_interval
+            }, RDRAM_REG_ADDR_SELECT => {
+                self.addr_select
+            }, RDRAM_REG_DEVICE_MANUF => {
+                self.device_manuf
+            }, _ => 0
+        }
+    }
+
+    /* Writes to the module's registers; the type and manufacturer are read-only. */
+    fn wreg(&mut self, reg: u32, value: u32) {
+        match reg {
+            RDRAM_REG_DEVICE_ID => {
+                self.device_id = value
+            }, RDRAM_REG_DELAY => {
+                self.delay = value
+            }, RDRAM_REG_MODE => {
+                self.mode = value
+            }, RDRAM_REG_REF_INTERVAL => {
+                self.ref_interval = value
+            }, RDRAM_REG_REF_ROW => {
+                self.ref_row = value
+            }, RDRAM_REG_RAS_INTERVAL => {
+                self.ras_interval = value
+            }, RDRAM_REG_MIN_INTERVAL => {
+                self.min_interval = value
+            }, RDRAM_REG_ADDR_SELECT => {
+                self.addr_select = value
+            }, _ => {
+
+            }
+        }
+    }
+}
+
+/* The RDRAM. Data accesses no module answers read as zero and are otherwise ignored. */
+pub struct Rdram {
+    /* the memory of every module, in daisy chain order */
+    pub mem: Box<[u8]>,
+
+    /* register files of the installed modules, in daisy chain order */
+    modules: Vec<Module>
+}
+
+impl Rdram {
+    pub fn new(size: usize) -> Rdram {
+        Rdram {
+            mem: vec![0; size].into_boxed_slice(),
+            modules: (0..size / RDRAM_MODULE_SIZE).map(Module::new).collect()
+        }
+    }
+
+    /* Returns the offset in memory of a data access, from the first module in the chain that answers it. */
+    fn decode(&self, paddr: u32, size: usize) -> Option<usize> {
+        let addr = paddr - RDRAM_MEM_START;
+        let row = (addr >> RDRAM_MEM_ID_SHIFT) & RDRAM_MEM_ID_MASK;
+        let offset = addr as usize % RDRAM_MODULE_SIZE;
+
+        if offset + size > RDRAM_MODULE_SIZE {
+            return None;
+        }
+
+        self.modules.iter().position(|m| m.selects(row)).map(|i| i * RDRAM_MODULE_SIZE + offset)
+    }
+
+    /* Returns the first module in the chain answering to the ID selected by a register address. */
+    fn module(&self, paddr: u32) -> Option<usize> {
+        let id = (paddr >> RDRAM_REG_ID_SHIFT) & RDRAM_REG_ID_MASK;
+        self.modules.iter().position(|m| m.id() == id)
+    }
+
+    /* Reads from the registers of the addressed module; nothing answers broadcast reads or unused IDs. */
+    fn rreg(&self, reg: u32) -> u32 {
+        if (reg & RDRAM_REG_BROADCAST) != 0 {
+            return 0;
+        }
+
+        match self.module(reg) {
+            Some(i) => self.modules[i].rreg(reg & 0x3FF),
+            None => 0
+        }
+    }
+
+    /* Writes to the registers of the addressed module, or of every module for a broadcast. */
+    fn wreg(&mut self, reg: u32, value: u32) {
+        if (reg & RDRAM_REG_BROADCAST) != 0 {
+            for m in self.modules.iter_mut() {
+                m.wreg(reg & 0x3FF, value);
+            }
+        } else if let Some(i) = self.module(reg) {
+            self.modules[i].wreg(reg & 0x3FF, value);
+        }
+    }
+}
+
+impl Device for Rdram {
+    fn read(&self, paddr: u32, size: usize) -> Result<u64, BusError> {
+        match paddr {
+            RDRAM_REG_START ..= RDRAM_REG_END => {
+                rreg(paddr, size, |a| self.rreg(a))
+            }, _ => {
+                match self.decode(paddr, size) {
+                    Some(offset) => rmem(offset as u32, size, &self.mem),
+                    None => Ok(0)
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, paddr: u32, size: usize, val: u64) -> Result<(), BusError> {
+        match paddr {
+            RDRAM_REG_START ..= RDRAM_REG_END => {
+                wreg(paddr, size, val, |a, v| self.wreg(a, v))
+            }, _ => {
+                match self.decode(paddr, size) {
+                    Some(offset) => wmem(offset as u32, size, val, &mut self.mem),
+                    None => Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Returns the address of a register of the module answering to an ID. */
+    fn reg(id: u32, reg: u32) -> u32 {
+        RDRAM_REG_START | (id << RDRAM_REG_ID_SHIFT) | reg
+    }
+
+    fn broadcast(reg: u32) -> u32 {
+        RDRAM_REG_START | RDRAM_REG_BROADCAST | reg
+    }
+
+    #[test]
+    fn idfield_layout() {
+        assert_eq!(idfield(0xFC00_0000), 0x3F);
+        assert_eq!(idfield(0x0080_0000), 0x40);
+        assert_eq!(idfield(0x0000_0180), 0x180);
+        assert_eq!(idfield(0x037F_FE7F), 0);
+
+        for id in 0..0x200 {
+            assert_eq!(idfield(idreg(id)), id);
+        }
+    }
+
+    #[test]
+    fn default_mapping() {
+        let mut r = Rdram::new(0x400000);
+
+        r.write(0x000004, 4, 0x1111_1111).unwrap();
+        r.write(0x200004, 4, 0x2222_2222).unwrap();
+        assert_eq!(rmem(0x000004, 4, &r.mem), Ok(0x1111_1111));
+        assert_eq!(rmem(0x200004, 4, &r.mem), Ok(0x2222_2222));
+
+        /* nothing is fitted past the end */
+        assert_eq!(r.read(0x400004, 4), Ok(0));
+    }
+
+    #[test]
+    fn broadcast_write() {
+        let mut r = Rdram::new(0x400000);
+
+        r.write(broadcast(RDRAM_REG_DELAY), 4, 0x2828_3838).unwrap();
+        assert_eq!(r.read(reg(0, RDRAM_REG_DELAY), 4), Ok(0x2828_3838));
+        assert_eq!(r.read(reg(2, RDRAM_REG_DELAY), 4), Ok(0x2828_3838));
+
+        /* nothing answers broadcast reads */
+        assert_eq!(r.read(broadcast(RDRAM_REG_DELAY), 4), Ok(0));
+    }
+
+    #[test]
+    fn module_register_write() {
+        let mut r = Rdram::new(0x400000);
+
+        r.write(reg(2, RDRAM_REG_REF_ROW), 4, 0x1234).unwrap();
+        assert_eq!(r.read(reg(2, RDRAM_REG_REF_ROW), 4), Ok(0x1234));
+        assert_eq!(r.read(reg(0, RDRAM_REG_REF_ROW), 4), Ok(0));
+
+        /* the type and manufacturer are read-only, and the mode reads back partly inverted */
+        r.write(reg(0, RDRAM_REG_DEVICE_MANUF), 4, 0).unwrap();
+        assert_eq!(r.read(reg(0, RDRAM_REG_DEVICE_MANUF), 4), Ok(0x0000_0500));
+        r.write(reg(0, RDRAM_REG_MODE), 4, 0).unwrap();
+        assert_eq!(r.read(reg(0, RDRAM_REG_MODE), 4), Ok(RDRAM_MODE_INVERTED as u64));
+    }
+
+    #[test]
+    fn decode_after_configuration() {
+        let mut r = Rdram::new(0x400000);
+
+        /* swap the two modules, moving the first out of the way before giving the second its ID */
+        r.write(reg(0, RDRAM_REG_DEVICE_ID), 4, idreg(4) as u64).unwrap();
+        r.write(reg(2, RDRAM_REG_DEVICE_ID), 4, idreg(0) as u64).unwrap();
+        r.write(reg(4, RDRAM_REG_DEVICE_ID), 4, idreg(2) as u64).unwrap();
+
+        r.write(0x000000, 4, 0xAAAA_AAAA).unwrap();
+        r.write(0x200000, 4, 0xBBBB_BBBB).unwrap();
+        assert_eq!(rmem(0x200000, 4, &r.mem), Ok(0xAAAA_AAAA));
+        assert_eq!(rmem(0x000000, 4, &r.mem), Ok(0xBBBB_BBBB));
+
+        /* AddressSelect swaps row bits before the comparison */
+        r.write(broadcast(RDRAM_REG_ADDR_SELECT), 4, idreg(2) as u64).unwrap();
+        assert_eq!(r.read(0x000000, 4), Ok(0xBBBB_BBBB));
+        assert_eq!(r.read(0x200000, 4), Ok(0xAAAA_AAAA));
+    }
+}
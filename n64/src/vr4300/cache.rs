@@ -0,0 +1,343 @@
+/* cache.rs - The VR4300's direct mapped primary instruction and data caches. */
+
+/*
+
+Primary caches
+
+  ICache:  16KB, 512 lines of 32 bytes, indexed by vaddr[13:5]
+  DCache:   8KB, 512 lines of 16 bytes, indexed by vaddr[12:4]
+
+  Each line is tagged with the frame of physical memory it holds (paddr[31:12]).
+  Through TagLo the tag appears as [27:8] PTagLo beside [7:6] PState, which holds
+  the valid bit and, for the data cache, the dirty bit.
+
+*/
+
+use crate::mc::Bus;
+use crate::mc::BusError;
+
+/* Cache geometries. */
+pub const ICACHE_LINES: usize = 512;
+pub const ICACHE_LINE_SIZE: usize = 32;
+pub const DCACHE_LINES: usize = 512;
+pub const DCACHE_LINE_SIZE: usize = 16;
+
+/* TagLo fields. */
+const TAGLO_PTAG: u64 = 0x0FFF_FF00;
+const TAGLO_V: u64 = 1 << 7;
+const TAGLO_D: u64 = 1 << 6;
+
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub valid: bool,
+    pub dirty: bool,
+    /* the physical frame the line was filled from */
+    pub ptag: u32,
+    pub data: Vec<u8>
+}
+
+pub struct Cache {
+    lines: Vec<Line>,
+    /* line size in bytes */
+    size: usize,
+    /* words of written back lines waiting to reach memory */
+    pending: Vec<(u32, u64)>
+}
+
+impl Cache {
+
+    pub fn new(lines: usize, size: usize) -> Cache {
+        Cache {
+            lines: vec![Line { valid: false, dirty: false, ptag: 0, data: vec![0; size] }; lines],
+            size,
+            pending: Vec::new()
+        }
+    }
+
+    /* Returns the line a virtual address indexes. */
+    fn index(&self, vaddr: u64) -> usize {
+        (vaddr as usize / self.size) % self.lines.len()
+    }
+
+    /* Returns the physical address of the first byte held by a line. */
+    fn base(&self, i: usize) -> u32 {
+        (self.lines[i].ptag << 12) | ((i * self.size) & 0xFFF) as u32
+    }
+
+    /* Determines whether the line indexed by a virtual address holds the physical address. */
+    pub fn hit(&self, vaddr: u64, paddr: u32) -> bool {
+        let l = &self.lines[self.index(vaddr)];
+        l.valid && l.ptag == (paddr >> 12)
+    }
+
+    /* Reads a big-endian value of 1, 2, 4 or 8 bytes from the line holding a virtual address. */
+    pub fn read(&self, vaddr: u64, size: usize) -> u64 {
+        let offset = vaddr as usize % self.size;
+        let data = &self.lines[self.index(vaddr)].data[offset..offset + size];
+
+        data.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+    }
+
+    /* Writes a big-endian value of 1, 2, 4 or 8 bytes to the line holding a virtual address. */
+    pub fn write(&mut self, vaddr: u64, val: u64, size: usize) {
+        let offset = vaddr as usize % self.size;
+        let i = self.index(vaddr);
+
+        self.lines[i].data[offset..offset + size].copy_from_slice(&val.to_be_bytes()[8 - size..]);
+        self.lines[i].dirty = true;
+    }
+
+    /* Fills the line indexed by a virtual address from memory, writing back the line it replaces.
+       A bus error leaves the line as it was. */
+    pub fn fill(&mut self, vaddr: u64, paddr: u32, bus: &Bus) -> Result<(), BusError> {
+        let i = self.index(vaddr);
+
+        let start = paddr & !(self.size as u32 - 1);
+        let mut data = vec![0; self.size];
+        for k in (0..self.size).step_by(4) {
+            let word = bus.read(start + k as u32, 4)? as u32;
+            data[k..k + 4].copy_from_slice(&word.to_be_bytes());
+        }
+
+        self.writeback(i);
+        self.lines[i].data = data;
+        self.lines[i].valid = true;
+        self.lines[i].dirty = false;
+        self.lines[i].ptag = paddr >> 12;
+        Ok(())
+    }
+
+    /* Queues the words of a valid line to be written to memory and marks it clean. */
+    fn flush(&mut self, i: usize) {
+        if !self.lines[i].valid {
+            return;
+        }
+
+        let base = self.base(i);
+        for k in (0..self.size).step_by(4) {
+            let word = self.lines[i].data[k..k + 4].iter().fold(0, |acc, &b| (acc << 8) | b as u64);
+            self.pending.push((base + k as u32, word));
+        }
+
+        self.lines[i].dirty = false;
+    }
+
+    /* Queues a line to be written to memory if it is dirty. */
+    fn writeback(&mut self, i: usize) {
+        if self.lines[i].dirty {
+            self.flush(i);
+        }
+    }
+
+    /* Writes the queued words of written back lines to memory; those that memory refuses are lost. */
+    pub fn drain(&mut self, bus: &mut Bus) {
+        for (paddr, word) in self.pending.drain(..) {
+            let _ = bus.write(paddr, 4, word);
+        }
+    }
+
+    /* Invalidates the line indexed by a virtual address. */
+    pub fn index_invalidate(&mut self, vaddr: u64) {
+        let i = self.index(vaddr);
+        self.lines[i].valid = false;
+    }
+
+    /* Invalidates the line indexed by a virtual address, writing it back first if it is dirty. */
+    pub fn index_writeback_invalidate(&mut self, vaddr: u64) {
+        let i = self.index(vaddr);
+        self.writeback(i);
+        self.lines[i].valid = false;
+    }
+
+    /* Returns the tag and state of the line indexed by a virtual address in the format of TagLo. */
+    pub fn load_tag(&self, vaddr: u64) -> u64 {
+        let l = &self.lines[self.index(vaddr)];
+
+        (((l.ptag as u64) << 8) & TAGLO_PTAG) |
+            if l.valid { TAGLO_V } else { 0 } |
+            if l.dirty { TAGLO_D } else { 0 }
+    }
+
+    /* Sets the tag and state of the line indexed by a virtual address from TagLo. */
+    pub fn store_tag(&mut self, vaddr: u64, taglo: u64) {
+        let i = self.index(vaddr);
+
+        self.lines[i].ptag = ((taglo & TAGLO_PTAG) >> 8) as u32;
+        self.lines[i].valid = (taglo & TAGLO_V) != 0;
+        self.lines[i].dirty = (taglo & TAGLO_D) != 0;
+    }
+
+    /* Claims the line for the physical address as valid and dirty without reading memory. */
+    pub fn create_dirty(&mut self, vaddr: u64, paddr: u32) {
+        let i = self.index(vaddr);
+
+        if !self.hit(vaddr, paddr) {
+            self.writeback(i);
+        }
+
+        self.lines[i].valid = true;
+        self.lines[i].dirty = true;
+        self.lines[i].ptag = paddr >> 12;
+    }
+
+    /* Invalidates the line if it holds the physical address, discarding any dirty data. */
+    pub fn hit_invalidate(&mut self, vaddr: u64, paddr: u32) {
+        if self.hit(vaddr, paddr) {
+            let i = self.index(vaddr);
+            self.lines[i].valid = false;
+        }
+    }
+
+    /* Writes back and invalidates the line if it holds the physical address. */
+    pub fn hit_writeback_invalidate(&mut self, vaddr: u64, paddr: u32) {
+        if self.hit(vaddr, paddr) {
+            self.index_writeback_invalidate(vaddr);
+        }
+    }
+
+    /* Writes back the line if it holds the physical address; the instruction cache always writes its line. */
+    pub fn hit_writeback(&mut self, vaddr: u64, paddr: u32, always: bool) {
+        if self.hit(vaddr, paddr) {
+            let i = self.index(vaddr);
+            if always {
+                self.flush(i);
+            } else {
+                self.writeback(i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Addresses 8KB apart share a data cache line. */
+    const ALIAS: u32 = 0x2000;
+
+    fn bus() -> Bus {
+        let mut bus = Bus::new(vec![0; 0x1000].into_boxed_slice(), vec![0; 0x7C0].into_boxed_slice(), 0x400000);
+        for k in (0..0x4000).step_by(4) {
+            bus.write(k, 4, k as u64).unwrap();
+        }
+        bus
+    }
+
+    fn dcache() -> Cache {
+        Cache::new(DCACHE_LINES, DCACHE_LINE_SIZE)
+    }
+
+    #[test]
+    fn line_fill() {
+        let bus = bus();
+        let mut c = dcache();
+
+        assert!(!c.hit(0x8000_0014, 0x14));
+        c.fill(0x8000_0014, 0x14, &bus).unwrap();
+        assert!(c.hit(0x8000_001C, 0x1C));
+        assert!(!c.hit(0x8000_0024, 0x24));
+        assert_eq!(c.read(0x8000_0010, 4), 0x10);
+        assert_eq!(c.read(0x8000_0018, 8), 0x0000_0018_0000_001C);
+
+        /* a fill that no device answers leaves the line alone */
+        assert_eq!(c.fill(0x8000_0010, 0x0490_0010, &bus), Err(BusError));
+        assert!(c.hit(0x8000_0010, 0x10));
+    }
+
+    #[test]
+    fn writeback_on_eviction() {
+        let mut bus = bus();
+        let mut c = dcache();
+
+        c.fill(0x8000_0010, 0x10, &bus).unwrap();
+        c.write(0x8000_0010, 0xAAAA_AAAA, 4);
+        assert_eq!(bus.read(0x10, 4), Ok(0x10));
+
+        /* replacing the dirty line writes it back */
+        c.fill(0x8000_0010 + ALIAS as u64, 0x10 + ALIAS, &bus).unwrap();
+        c.drain(&mut bus);
+        assert_eq!(bus.read(0x10, 4), Ok(0xAAAA_AAAA));
+        assert_eq!(bus.read(0x14, 4), Ok(0x14));
+        assert_eq!(c.read(0x8000_0010, 4), (0x10 + ALIAS) as u64);
+
+        /* clean lines are dropped */
+        c.fill(0x8000_0010, 0x10, &bus).unwrap();
+        assert!(c.pending.is_empty());
+    }
+
+    #[test]
+    fn index_ops() {
+        let mut bus = bus();
+        let mut c = dcache();
+
+        c.fill(0x8000_0010, 0x10, &bus).unwrap();
+        c.index_invalidate(0x8000_0010);
+        assert!(!c.hit(0x8000_0010, 0x10));
+
+        c.fill(0x8000_0010, 0x10, &bus).unwrap();
+        c.write(0x8000_0010, 0xAAAA_AAAA, 4);
+        c.index_writeback_invalidate(0x8000_0010 + ALIAS as u64);
+        c.drain(&mut bus);
+        assert!(!c.hit(0x8000_0010, 0x10));
+        assert_eq!(bus.read(0x10, 4), Ok(0xAAAA_AAAA));
+
+        /* the tag and state pass through TagLo */
+        c.store_tag(0x8000_0020, (0x12345 << 8) | TAGLO_V | TAGLO_D);
+        assert!(c.hit(0x8000_0020, 0x1234_5020));
+        assert_eq!(c.load_tag(0x8000_0020), (0x12345 << 8) | TAGLO_V | TAGLO_D);
+    }
+
+    #[test]
+    fn hit_ops() {
+        let mut bus = bus();
+        let mut c = dcache();
+
+        /* Create Dirty Exclusive claims the line without reading memory */
+        c.create_dirty(0x8000_0010, 0x10);
+        assert!(c.hit(0x8000_0010, 0x10));
+        assert_eq!(c.load_tag(0x8000_0010) & (TAGLO_V | TAGLO_D), TAGLO_V | TAGLO_D);
+        c.write(0x8000_0010, 0xAAAA_AAAA, 4);
+
+        /* hit operations ignore addresses the line does not hold */
+        c.hit_invalidate(0x8000_0010, 0x10 + ALIAS);
+        c.hit_writeback(0x8000_0010, 0x10 + ALIAS, false);
+        assert!(c.hit(0x8000_0010, 0x10));
+        assert!(c.pending.is_empty());
+
+        /* Hit Writeback keeps the line, now clean */
+        c.hit_writeback(0x8000_0010, 0x10, false);
+        c.drain(&mut bus);
+        assert_eq!(bus.read(0x10, 4), Ok(0xAAAA_AAAA));
+        assert!(c.hit(0x8000_0010, 0x10));
+        assert_eq!(c.load_tag(0x8000_0010) & TAGLO_D, 0);
+
+        /* Hit Writeback Invalidate writes back dirty data and drops the line */
+        c.write(0x8000_0010, 0xBBBB_BBBB, 4);
+        c.hit_writeback_invalidate(0x8000_0010, 0x10);
+        c.drain(&mut bus);
+        assert_eq!(bus.read(0x10, 4), Ok(0xBBBB_BBBB));
+        assert!(!c.hit(0x8000_0010, 0x10));
+
+        /* Hit Invalidate discards dirty data */
+        c.fill(0x8000_0010, 0x10, &bus).unwrap();
+        c.write(0x8000_0010, 0xCCCC_CCCC, 4);
+        c.hit_invalidate(0x8000_0010, 0x10);
+        c.drain(&mut bus);
+        assert_eq!(bus.read(0x10, 4), Ok(0xBBBB_BBBB));
+        assert!(!c.hit(0x8000_0010, 0x10));
+    }
+
+    #[test]
+    fn icache_hit_writeback() {
+        let mut bus = bus();
+        let mut c = Cache::new(ICACHE_LINES, ICACHE_LINE_SIZE);
+
+        /* the instruction cache writes its line back whether or not it is dirty */
+        c.fill(0x8000_0020, 0x20, &bus).unwrap();
+        bus.write(0x20, 4, 0).unwrap();
+        c.hit_writeback(0x8000_0020, 0x20, true);
+        c.drain(&mut bus);
+        assert_eq!(bus.read(0x20, 4), Ok(0x20));
+        assert_eq!(bus.read(0x3C, 4), Ok(0x3C));
+    }
+}
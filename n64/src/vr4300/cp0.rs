@@ -16,76 +16,505 @@ COP0
 */
 
 pub const CP0_NAMES: [&'static str; GPR_SIZE] = [
-    "Index",       "BadVAddr",    "Config",      "RESERVED",
-    "Random",      "Count",       "LLAddr",      "RESERVED",
-    "EntryLo0",    "EntryHi",     "WatchLo",     "PErr",
-    "EntryLo1",    "Compare",     "WatchHi",     "CacheErr",
-    "Context",     "Status",      "XContext",    "TagLo",
-    "PageMask",    "Cause",       "RESERVED",    "TagHi",
-    "Wired",       "EPC",         "RESERVED",    "ErrorEPC",
-    "RESERVED",    "PRevID",      "RESERVED",    "RESERVED"
+    "Index",       "Random",      "EntryLo0",    "EntryLo1",
+    "Context",     "PageMask",    "Wired",       "RESERVED",
+    "BadVAddr",    "Count",       "EntryHi",     "Compare",
+    "Status",      "Cause",       "EPC",         "PRevID",
+    "Config",      "LLAddr",      "WatchLo",     "WatchHi",
+    "XContext",    "RESERVED",    "RESERVED",    "RESERVED",
+    "RESERVED",    "RESERVED",    "PErr",        "CacheErr",
+    "TagLo",       "TagHi",       "ErrorEPC",    "RESERVED"
 ];
 
 use super::*;
 
+use crate::mc::*;
+
+/* COP0 register numbers. */
+pub const CP0_INDEX: usize = 0x00;
+pub const CP0_RANDOM: usize = 0x01;
+pub const CP0_ENTRYLO0: usize = 0x02;
+pub const CP0_ENTRYLO1: usize = 0x03;
+pub const CP0_CONTEXT: usize = 0x04;
+pub const CP0_PAGEMASK: usize = 0x05;
+pub const CP0_WIRED: usize = 0x06;
+pub const CP0_BADVADDR: usize = 0x08;
+pub const CP0_COUNT: usize = 0x09;
+pub const CP0_ENTRYHI: usize = 0x0A;
+pub const CP0_COMPARE: usize = 0x0B;
+pub const CP0_STATUS: usize = 0x0C;
+pub const CP0_CAUSE: usize = 0x0D;
+pub const CP0_EPC: usize = 0x0E;
+pub const CP0_PRID: usize = 0x0F;
+pub const CP0_CONFIG: usize = 0x10;
+pub const CP0_LLADDR: usize = 0x11;
+pub const CP0_WATCHLO: usize = 0x12;
+pub const CP0_WATCHHI: usize = 0x13;
+pub const CP0_XCONTEXT: usize = 0x14;
+pub const CP0_PERR: usize = 0x1A;
+pub const CP0_TAGLO: usize = 0x1C;
+pub const CP0_ERROREPC: usize = 0x1E;
+
+/* Processor revision reported by PRId and the power-on value of Config. */
+const PRID_VR4300: u64 = 0x0000_0B22;
+const CONFIG_RESET: u64 = 0x7006_E463;
+
+/* Index register probe failure bit. */
+const INDEX_P: u64 = 1 << 31;
+
+/* Status register fields. */
+const STATUS_IE: u64 = 1 << 0;
+const STATUS_EXL: u64 = 1 << 1;
+const STATUS_ERL: u64 = 1 << 2;
+const STATUS_KSU: u64 = 0x3 << 3;
+const STATUS_KSU_SHIFT: u64 = 3;
+const STATUS_UX: u64 = 1 << 5;
+const STATUS_SX: u64 = 1 << 6;
+const STATUS_KX: u64 = 1 << 7;
+const STATUS_SR: u64 = 1 << 20;
+const STATUS_TS: u64 = 1 << 21;
+const STATUS_BEV: u64 = 1 << 22;
+const STATUS_FR: u64 = 1 << 26;
+const STATUS_CU_SHIFT: u64 = 28;
+
+/* Cause register fields. */
+const CAUSE_EXCCODE: u64 = 0x1F << 2;
+const CAUSE_IP: u64 = 0xFF << 8;
+const CAUSE_IP_SW: u64 = 0x3 << 8;
+const CAUSE_CE: u64 = 0x3 << 28;
+const CAUSE_BD: u64 = 1 << 31;
+
+/* WatchLo fields; WatchHi holds physical address bits the VR4300 never drives. */
+const WATCHLO_PADDR: u64 = 0xFFFF_FFF8;
+const WATCHLO_R: u64 = 1 << 1;
+const WATCHLO_W: u64 = 1 << 0;
+
+/* Interrupt line of the Count/Compare timer. */
+const IP_TIMER: usize = 7;
+
+/* Context and XContext BadVPN2 fields. */
+const CONTEXT_BADVPN2: u64 = 0x0000_0000_007F_FFF0;
+const XCONTEXT_BADVPN2: u64 = 0x0000_0001_FFFF_FFF0;
+
+/* Bits of each register that software may change through MTC0/DMTC0; the rest are read-only. */
+const INDEX_WMASK: u64 = 0x0000_0000_0000_001F;
+const WIRED_WMASK: u64 = 0x0000_0000_0000_001F;
+const CONTEXT_WMASK: u64 = 0xFFFF_FFFF_FF80_0000;
+const XCONTEXT_WMASK: u64 = 0xFFFF_FFFE_0000_0000;
+const STATUS_WMASK: u64 = 0x0000_0000_FF57_FFFF;
+const CONFIG_WMASK: u64 = 0x0000_0000_0F00_800F;
+const WATCHLO_WMASK: u64 = 0x0000_0000_FFFF_FFFB;
+const WATCHHI_WMASK: u64 = 0x0000_0000_0000_000F;
+const PERR_WMASK: u64 = 0x0000_0000_0000_00FF;
+const TAGLO_WMASK: u64 = 0x0000_0000_0FFF_FFC0;
+const WORD_WMASK: u64 = 0x0000_0000_FFFF_FFFF;
+const DWORD_WMASK: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+
+/* xkphys addresses carry a cache attribute above a 32-bit physical address; the bits between must be zero. */
+const XKPHYS_PADDR: u64 = 0x0000_0000_FFFF_FFFF;
+const XKPHYS_HOLE: u64 = 0x07FF_FFFF_0000_0000;
+const XKPHYS_C_SHIFT: u64 = 59;
+
+/* Config.K0 selects whether KSEG0 is cached. */
+const CONFIG_K0: u64 = 0x7;
+
+/* The cache algorithm encoding for uncached accesses, as used by Config.K0, EntryLo.C and xkphys. */
+const CACHE_UNCACHED: u64 = 2;
+
+/* Exception vectors. */
+const VEC_BASE: u64 = 0xFFFF_FFFF_8000_0000;
+const VEC_BASE_BEV: u64 = 0xFFFF_FFFF_BFC0_0200;
+const VEC_TLB_REFILL: u64 = 0x000;
+const VEC_XTLB_REFILL: u64 = 0x080;
+const VEC_GENERAL: u64 = 0x180;
+const VEC_RESET: u64 = 0xFFFF_FFFF_BFC0_0000;
+
+/* Operating modes, selected by Status.KSU and overridden to kernel by EXL or ERL. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Mode {
+    Kernel,
+    Supervisor,
+    User
+}
+
 pub struct CP0 {
-    /* the 32-bit cop0 general purpose registers */
-    gpr: [u32; GPR_SIZE],
+    /* the cop0 general purpose registers */
+    gpr: [u64; GPR_SIZE],
+
+    /* joint translation lookaside buffer */
+    pub tlb: Tlb,
+
+    /* Count advances every other pipeline cycle */
+    odd_cycle: bool,
+
+    /* Random only steps on TLBWR rather than every cycle, so replacement is independent of timing (for replay) */
+    fixed_random: bool
 }
 
 impl CP0 {
 
     pub fn new() -> CP0 {
+        /* zero-initialize the cop0 registers, except for the fixed revision and configuration */
+        let mut gpr = [0; GPR_SIZE];
+        gpr[CP0_RANDOM] = (TLB_SIZE - 1) as u64;
+        gpr[CP0_PRID] = PRID_VR4300;
+        gpr[CP0_CONFIG] = CONFIG_RESET;
+
         CP0 {
-            /* zero-initialize the cop0 registers */
-            gpr: [0; GPR_SIZE]
+            gpr,
+
+            tlb: Tlb::new(),
+
+            odd_cycle: false,
+
+            fixed_random: false
         }
     }
 
     /* reads from a cop0 register */
-    pub fn rgpr(&self, reg: usize) -> u32 {
+    pub fn rgpr(&self, reg: usize) -> u64 {
         self.gpr[reg]
     }
 
-    /* writes to a cop0 register */
-    pub fn wgpr(&mut self, val: u32, reg: usize) {
-        self.gpr[reg] = val;
+    /* Returns the bits of a cop0 register that software may write. */
+    fn wmask(reg: usize) -> u64 {
+        match reg {
+            CP0_INDEX => INDEX_WMASK,
+            CP0_ENTRYLO0 | CP0_ENTRYLO1 => ENTRYLO_MASK,
+            CP0_CONTEXT => CONTEXT_WMASK,
+            CP0_PAGEMASK => PAGEMASK_MASK,
+            CP0_WIRED => WIRED_WMASK,
+            CP0_COUNT | CP0_COMPARE | CP0_LLADDR => WORD_WMASK,
+            CP0_ENTRYHI => ENTRYHI_MASK,
+            CP0_STATUS => STATUS_WMASK,
+            CP0_CAUSE => CAUSE_IP_SW,
+            CP0_EPC | CP0_ERROREPC => DWORD_WMASK,
+            CP0_CONFIG => CONFIG_WMASK,
+            CP0_WATCHLO => WATCHLO_WMASK,
+            CP0_WATCHHI => WATCHHI_WMASK,
+            CP0_XCONTEXT => XCONTEXT_WMASK,
+            CP0_PERR => PERR_WMASK,
+            CP0_TAGLO => TAGLO_WMASK,
+
+            /* Random, BadVAddr, PRId, CacheErr, TagHi and the reserved registers are read-only */
+            _ => 0
+        }
+    }
+
+    /* Writes to a cop0 register as MTC0/DMTC0 would, leaving its read-only bits untouched. */
+    pub fn wgpr(&mut self, val: u64, reg: usize) {
+        let mask = CP0::wmask(reg);
+        self.gpr[reg] = (self.gpr[reg] & !mask) | (val & mask);
+
+        match reg {
+            CP0_COMPARE => {
+                /* writing Compare acknowledges the timer interrupt */
+                self.set_ip(IP_TIMER, false);
+            }, CP0_WIRED => {
+                /* writing Wired resets Random to the upper bound */
+                self.gpr[CP0_RANDOM] = (TLB_SIZE - 1) as u64;
+            }, _ => {
+
+            }
+        }
+    }
+
+    /* Advances Count at half the pipeline clock and raises the timer interrupt when it reaches Compare.
+       Random counts down every cycle unless it is fixed. */
+    pub fn tick(&mut self) {
+        if !self.fixed_random {
+            self.step_random();
+        }
+
+        self.odd_cycle = !self.odd_cycle;
+
+        if self.odd_cycle {
+            return;
+        }
+
+        let count = (self.gpr[CP0_COUNT] as u32).wrapping_add(1);
+        self.gpr[CP0_COUNT] = count as u64;
+
+        if count == self.gpr[CP0_COMPARE] as u32 {
+            self.set_ip(IP_TIMER, true);
+        }
+    }
+
+    /* Decrements Random, wrapping from Wired back to the last TLB entry. */
+    fn step_random(&mut self) {
+        let random = self.gpr[CP0_RANDOM];
+
+        self.gpr[CP0_RANDOM] = if random <= self.gpr[CP0_WIRED] {
+            (TLB_SIZE - 1) as u64
+        } else {
+            random - 1
+        };
+    }
+
+    /* the address space identifier of the running process */
+    fn asid(&self) -> u8 {
+        self.gpr[CP0_ENTRYHI] as u8
     }
 
+    /* Converts a virtual address to a physical address, checking it against the current operating mode.
+       Also returns whether the address is cached. */
+    pub fn vtop(&self, vaddr: u64, write: bool) -> Result<(u32, bool), Exc> {
+        let mode = self.mode();
+
+        /* 32-bit addresses are sign-extended to 64 bits before being compared with the segments and the TLB. */
+        let vaddr = if self.ext(mode) { vaddr } else { vaddr as u32 as i32 as i64 as u64 };
+
+        let erl = (self.gpr[CP0_STATUS] & STATUS_ERL) != 0;
+        let kernel = mode == Mode::Kernel;
+        let supervisor = mode != Mode::User;
+
+        let err = if write { Exc::AdES(vaddr) } else { Exc::AdEL(vaddr) };
+
+        match vaddr {
+            0 ..= 0x7FFF_FFFF if kernel && erl => {
+                /* kuseg is unmapped while handling an error. */
+                Ok((vaddr as u32, false))
+            }, XUSEG_START ..= XUSEG_END => {
+                /* useg, suseg and kuseg, extended to xuseg, xsuseg and xkuseg, are accessible in all modes. */
+                self.translate(vaddr, write)
+            }, XSSEG_START ..= XSSEG_END if supervisor => {
+                /* xsseg and xksseg are mapped through the TLB. */
+                self.translate(vaddr, write)
+            }, XKPHYS_START ..= XKPHYS_END if kernel => {
+                /* xkphys is a direct window onto the physical address space. */
+                if (vaddr & XKPHYS_HOLE) != 0 {
+                    Err(err)
+                } else {
+                    Ok(((vaddr & XKPHYS_PADDR) as u32, ((vaddr >> XKPHYS_C_SHIFT) & 0x7) != CACHE_UNCACHED))
+                }
+            }, XKSEG_START ..= XKSEG_END if kernel => {
+                /* xkseg is mapped through the TLB. */
+                self.translate(vaddr, write)
+            }, CKSEG0_START ..= CKSEG0_END if kernel => {
+                /* Direct mapped segment KSEG0. */
+                Ok(((vaddr - CKSEG0_START) as u32, (self.gpr[CP0_CONFIG] & CONFIG_K0) != CACHE_UNCACHED))
+            }, CKSEG1_START ..= CKSEG1_END if kernel => {
+                /* Direct mapped segment KSEG1. */
+                Ok(((vaddr - CKSEG1_START) as u32, false))
+            }, CKSSEG_START ..= CKSSEG_END if supervisor => {
+                /* sseg and ksseg are mapped through the TLB. */
+                self.translate(vaddr, write)
+            }, CKSEG3_START ..= CKSEG3_END if kernel => {
+                /* KSEG3 is mapped through the TLB. */
+                self.translate(vaddr, write)
+            }, _ => {
+                /* the address is outside the segments of the current mode */
+                Err(err)
+            }
+        }
+    }
+
+    /* Maps a virtual address through the TLB. */
+    fn translate(&self, vaddr: u64, write: bool) -> Result<(u32, bool), Exc> {
+        self.tlb.translate(vaddr, self.asid(), write).map(|(paddr, c)| (paddr, c as u64 != CACHE_UNCACHED)).map_err(|f| {
+            match (f, write) {
+                (TlbFault::Modified, _) => Exc::Mod(vaddr),
+                (_, false) => Exc::Tlbl(vaddr, f == TlbFault::Miss),
+                (_, true) => Exc::Tlbs(vaddr, f == TlbFault::Miss)
+            }
+        })
+    }
+
+    /* Determines whether a load or store of a physical address hits the watchpoint in WatchLo/WatchHi.
+       Watch exceptions are not taken while handling another exception. */
+    pub fn watch(&self, paddr: u32, store: bool) -> bool {
+        let lo = self.gpr[CP0_WATCHLO];
+        let enabled = if store { WATCHLO_W } else { WATCHLO_R };
+
+        (lo & enabled) != 0 &&
+            (paddr as u64 & WATCHLO_PADDR) == (lo & WATCHLO_PADDR) &&
+            self.gpr[CP0_WATCHHI] == 0 &&
+            (self.gpr[CP0_STATUS] & (STATUS_EXL | STATUS_ERL)) == 0
+    }
+
+    /* Drives one of the external interrupt lines (IP2 - IP7) into Cause. */
+    pub fn set_ip(&mut self, ip: usize, level: bool) {
+        if level {
+            self.gpr[CP0_CAUSE] |= 1 << (8 + ip);
+        } else {
+            self.gpr[CP0_CAUSE] &= !(1 << (8 + ip));
+        }
+    }
+
+    /* Determines whether an unmasked interrupt is pending and interrupts are enabled. */
+    pub fn intr(&self) -> bool {
+        let status = self.gpr[CP0_STATUS];
+
+        (self.gpr[CP0_CAUSE] & status & CAUSE_IP) != 0 &&
+            (status & STATUS_IE) != 0 &&
+            (status & (STATUS_EXL | STATUS_ERL)) == 0
+    }
+
+    /* Selects whether Random only steps on TLBWR, for replay. */
+    pub fn set_fixed_random(&mut self, fixed: bool) {
+        self.fixed_random = fixed;
+    }
+
+    /* Determines whether Status.CU marks a coprocessor usable. */
+    pub fn cu(&self, cop: u8) -> bool {
+        (self.gpr[CP0_STATUS] & (1 << (STATUS_CU_SHIFT + cop as u64))) != 0
+    }
+
+    /* Returns the current operating mode; the undefined KSU encoding is treated as user mode. */
+    pub fn mode(&self) -> Mode {
+        let status = self.gpr[CP0_STATUS];
+
+        if (status & (STATUS_EXL | STATUS_ERL)) != 0 {
+            return Mode::Kernel;
+        }
+
+        match (status & STATUS_KSU) >> STATUS_KSU_SHIFT {
+            0 => Mode::Kernel,
+            1 => Mode::Supervisor,
+            _ => Mode::User
+        }
+    }
+
+    /* Determines whether the processor is in kernel mode. */
+    pub fn kernel(&self) -> bool {
+        self.mode() == Mode::Kernel
+    }
+
+    /* Determines whether Status.KX, SX or UX enables 64-bit addressing in the given mode. */
+    fn ext(&self, mode: Mode) -> bool {
+        let bit = match mode {
+            Mode::Kernel => STATUS_KX,
+            Mode::Supervisor => STATUS_SX,
+            Mode::User => STATUS_UX
+        };
+
+        (self.gpr[CP0_STATUS] & bit) != 0
+    }
+
+    /* Determines whether a coprocessor's instructions may be executed; cop0 is always usable in kernel mode. */
+    pub fn usable(&self, cop: u8) -> bool {
+        self.cu(cop) || (cop == 0 && self.kernel())
+    }
+
+    /* Determines whether Status.FR selects the 32 64-bit FPU register layout. */
+    pub fn fr(&self) -> bool {
+        (self.gpr[CP0_STATUS] & STATUS_FR) != 0
+    }
+
+    /* Records an exception taken by the instruction at pc and returns the exception vector. */
+    pub fn exception(&mut self, e: Exc, pc: u64, bd: bool) -> u64 {
+
+        let status = self.gpr[CP0_STATUS];
+        let exl = (status & STATUS_EXL) != 0;
+
+        /* refills taken with 64-bit addressing enabled for the faulting mode use the XTLB handler */
+        let xtlb = self.ext(self.mode());
+
+        /* the faulting address is reported through BadVAddr, Context and XContext */
+        if let Some(vaddr) = e.vaddr() {
+            self.gpr[CP0_BADVADDR] = vaddr;
+            self.gpr[CP0_CONTEXT] = (self.gpr[CP0_CONTEXT] & !CONTEXT_BADVPN2) | ((vaddr >> 9) & CONTEXT_BADVPN2);
+            self.gpr[CP0_XCONTEXT] = (self.gpr[CP0_XCONTEXT] & !XCONTEXT_BADVPN2) | ((vaddr >> 9) & 0x7FFF_FFF0) | (((vaddr >> 62) & 0x3) << 31);
+
+            /* TLB exceptions load the VPN2 of the faulting address into EntryHi for the handler */
+            if e.tlb() {
+                self.gpr[CP0_ENTRYHI] = (vaddr & ENTRYHI_VPN2) | self.asid() as u64;
+            }
+        }
+
+        let ce = match e {
+            Exc::CpU(n) => n as u64,
+            _ => 0
+        };
+
+        self.gpr[CP0_CAUSE] = (self.gpr[CP0_CAUSE] & !(CAUSE_EXCCODE | CAUSE_CE)) | (e.code() << 2) | (ce << 28);
+
+        /* EPC and BD are only updated when not already handling an exception */
+        if !exl {
+            if bd {
+                self.gpr[CP0_EPC] = pc.wrapping_sub(4);
+                self.gpr[CP0_CAUSE] |= CAUSE_BD;
+            } else {
+                self.gpr[CP0_EPC] = pc;
+                self.gpr[CP0_CAUSE] &= !CAUSE_BD;
+            }
+        }
+
+        self.gpr[CP0_STATUS] |= STATUS_EXL;
+
+        let base = if (status & STATUS_BEV) != 0 { VEC_BASE_BEV } else { VEC_BASE };
+        let offset = match (e.refill() && !exl, xtlb) {
+            (true, false) => VEC_TLB_REFILL,
+            (true, true) => VEC_XTLB_REFILL,
+            (false, _) => VEC_GENERAL
+        };
+
+        base + offset
+    }
+
+    /* Records a non-maskable interrupt (soft reset) at pc and returns the reset vector. */
+    pub fn nmi(&mut self, pc: u64, bd: bool) -> u64 {
+        self.gpr[CP0_ERROREPC] = if bd { pc.wrapping_sub(4) } else { pc };
+        self.gpr[CP0_STATUS] = (self.gpr[CP0_STATUS] & !STATUS_TS) | STATUS_ERL | STATUS_SR | STATUS_BEV;
+
+        VEC_RESET
+    }
+
+    /* Returns from an exception, yielding the address at which to resume execution. */
+    pub fn eret(&mut self) -> u64 {
+        if (self.gpr[CP0_STATUS] & STATUS_ERL) != 0 {
+            self.gpr[CP0_STATUS] &= !STATUS_ERL;
+            self.gpr[CP0_ERROREPC]
+        } else {
+            self.gpr[CP0_STATUS] &= !STATUS_EXL;
+            self.gpr[CP0_EPC]
+        }
+    }
+
+    /* Reads the TLB entry selected by Index into EntryHi, EntryLo0, EntryLo1 and PageMask. */
+    fn tlbr(&mut self) {
+        let e = self.tlb.read(self.gpr[CP0_INDEX] as usize);
+        let g = if e.g { ENTRYLO_G } else { 0 };
+
+        self.gpr[CP0_PAGEMASK] = e.mask;
+        self.gpr[CP0_ENTRYHI] = e.hi;
+        self.gpr[CP0_ENTRYLO0] = e.lo0 | g;
+        self.gpr[CP0_ENTRYLO1] = e.lo1 | g;
+    }
+
+    /* Writes EntryHi, EntryLo0, EntryLo1 and PageMask into the TLB entry at the given index. */
+    fn tlbw(&mut self, index: usize) {
+        let e = TlbEntry::new(self.gpr[CP0_PAGEMASK], self.gpr[CP0_ENTRYHI], self.gpr[CP0_ENTRYLO0], self.gpr[CP0_ENTRYLO1]);
+        self.tlb.write(index, e);
+    }
+
+    /* Searches the TLB for an entry matching EntryHi and stores its index to Index. */
+    fn tlbp(&mut self) {
+        self.gpr[CP0_INDEX] = match self.tlb.probe(self.gpr[CP0_ENTRYHI]) {
+            Some(i) => i as u64,
+            None => INDEX_P
+        };
+    }
+
+    /* Executes the TLB instructions; moves to and from cop0 go through rgpr/wgpr in the pipeline. */
     pub fn exec(&mut self, i: Inst) {
 
         match i.op() {
 
-            Op::Mf => {
-                unimplemented!()
-            }, Op::Dmf => {
-                unimplemented!()
-            }, Op::Cf => {
-                unimplemented!()
-            }, Op::Mt => {
-                unimplemented!()
-            }, Op::Dmt => {
-                unimplemented!()
-            }, Op::Ct => {
-                unimplemented!();
-            }, Op::Bcf => {
-                unimplemented!();
-            }, Op::Bct => {
-                unimplemented!();
-            }, Op::Bcfl => {
-                unimplemented!();
-            }, Op::Bctl => {
-                unimplemented!();
-            }, Op::Tlbr => {
-                unimplemented!();
+            Op::Tlbr => {
+                self.tlbr();
             }, Op::Tlbwi => {
-                unimplemented!();
+                let index = self.gpr[CP0_INDEX] as usize;
+                self.tlbw(index);
             }, Op::Tlbwr => {
-                unimplemented!();
+                let index = self.gpr[CP0_RANDOM] as usize;
+                self.tlbw(index);
+
+                if self.fixed_random {
+                    self.step_random();
+                }
             }, Op::Tlbp => {
-                unimplemented!();
-            }, Op::Eret => {
-                unimplemented!();
+                self.tlbp();
             }, _ => {
 
             }
@@ -95,3 +524,261 @@ impl CP0 {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TLBR: Inst = Inst(0x4200_0001);
+    const TLBWI: Inst = Inst(0x4200_0002);
+    const TLBP: Inst = Inst(0x4200_0008);
+
+    /* Builds an EntryLo value mapping a physical page with the given flags. */
+    fn lo(paddr: u32, flags: u64) -> u64 {
+        ((paddr as u64 >> 12) << 6) | flags
+    }
+
+    /* Writes a TLB entry through the CP0 registers, as TLBWI would in a handler. */
+    fn tlbwi(cp0: &mut CP0, index: u64, hi: u64, lo0: u64, lo1: u64) {
+        cp0.wgpr(index, CP0_INDEX);
+        cp0.wgpr(0, CP0_PAGEMASK);
+        cp0.wgpr(hi, CP0_ENTRYHI);
+        cp0.wgpr(lo0, CP0_ENTRYLO0);
+        cp0.wgpr(lo1, CP0_ENTRYLO1);
+        cp0.exec(TLBWI);
+    }
+
+    fn paddr(r: Result<(u32, bool), Exc>) -> Result<u32, Exc> {
+        r.map(|(paddr, _)| paddr)
+    }
+
+    #[test]
+    fn tlb_translation() {
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 3, 0x0040_0000, lo(0x0010_0000, ENTRYLO_V), lo(0x0020_0000, ENTRYLO_V | ENTRYLO_D));
+
+        /* the even and odd pages of the pair map separately */
+        assert_eq!(paddr(cp0.vtop(0x0040_0123, false)), Ok(0x0010_0123));
+        assert_eq!(paddr(cp0.vtop(0x0040_1FFC, true)), Ok(0x0020_0FFC));
+
+        /* TLBR reads the entry back */
+        cp0.wgpr(0, CP0_ENTRYHI);
+        cp0.exec(TLBR);
+        assert_eq!(cp0.rgpr(CP0_ENTRYHI), 0x0040_0000);
+        assert_eq!(cp0.rgpr(CP0_ENTRYLO0), lo(0x0010_0000, ENTRYLO_V));
+    }
+
+    #[test]
+    fn tlb_asid() {
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 0, 0x0040_0005, lo(0x0010_0000, ENTRYLO_V), 0);
+
+        /* a non-global entry only matches its own address space */
+        cp0.wgpr(0x05, CP0_ENTRYHI);
+        assert_eq!(paddr(cp0.vtop(0x0040_0000, false)), Ok(0x0010_0000));
+        cp0.wgpr(0x06, CP0_ENTRYHI);
+        assert_eq!(paddr(cp0.vtop(0x0040_0000, false)), Err(Exc::Tlbl(0x0040_0000, true)));
+
+        /* global entries match every address space */
+        tlbwi(&mut cp0, 0, 0x0040_0005, lo(0x0010_0000, ENTRYLO_V | ENTRYLO_G), ENTRYLO_G);
+        cp0.wgpr(0x06, CP0_ENTRYHI);
+        assert_eq!(paddr(cp0.vtop(0x0040_0000, false)), Ok(0x0010_0000));
+    }
+
+    #[test]
+    fn tlb_probe() {
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 7, 0x0040_0000, lo(0x0010_0000, ENTRYLO_V), 0);
+
+        cp0.wgpr(0x0040_1000, CP0_ENTRYHI);
+        cp0.exec(TLBP);
+        assert_eq!(cp0.rgpr(CP0_INDEX), 7);
+
+        cp0.wgpr(0x0080_0000, CP0_ENTRYHI);
+        cp0.exec(TLBP);
+        assert_eq!(cp0.rgpr(CP0_INDEX) & INDEX_P, INDEX_P);
+    }
+
+    #[test]
+    fn tlb_exceptions() {
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 0, 0x0040_0000, lo(0x0010_0000, ENTRYLO_V), lo(0x0020_0000, 0));
+
+        /* no entry: a refill, reported through BadVAddr and EntryHi and taken at the refill vector */
+        let e = cp0.vtop(0x0080_0010, false).unwrap_err();
+        assert_eq!(e, Exc::Tlbl(0x0080_0010, true));
+        assert_eq!(cp0.exception(e, 0xFFFF_FFFF_8000_0100, false), VEC_BASE + VEC_TLB_REFILL);
+        assert_eq!(cp0.rgpr(CP0_BADVADDR), 0x0080_0010);
+        assert_eq!(cp0.rgpr(CP0_ENTRYHI) & ENTRYHI_VPN2, 0x0080_0000);
+        assert_eq!((cp0.rgpr(CP0_CAUSE) & CAUSE_EXCCODE) >> 2, 2);
+
+        /* an invalid page is taken at the general vector */
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 0, 0x0040_0000, lo(0x0010_0000, ENTRYLO_V), lo(0x0020_0000, 0));
+        let e = cp0.vtop(0x0040_1000, true).unwrap_err();
+        assert_eq!(e, Exc::Tlbs(0x0040_1000, false));
+        assert_eq!(cp0.exception(e, 0xFFFF_FFFF_8000_0100, false), VEC_BASE + VEC_GENERAL);
+        assert_eq!((cp0.rgpr(CP0_CAUSE) & CAUSE_EXCCODE) >> 2, 3);
+
+        /* a store to a clean page is a modification */
+        let mut cp0 = CP0::new();
+        tlbwi(&mut cp0, 0, 0x0040_0000, lo(0x0010_0000, ENTRYLO_V), 0);
+        assert_eq!(paddr(cp0.vtop(0x0040_0000, false)), Ok(0x0010_0000));
+        let e = cp0.vtop(0x0040_0000, true).unwrap_err();
+        assert_eq!(e, Exc::Mod(0x0040_0000));
+        assert_eq!(cp0.exception(e, 0xFFFF_FFFF_8000_0100, false), VEC_BASE + VEC_GENERAL);
+        assert_eq!((cp0.rgpr(CP0_CAUSE) & CAUSE_EXCCODE) >> 2, 1);
+    }
+
+    #[test]
+    fn exception_vectors() {
+        let mut cp0 = CP0::new();
+
+        /* EPC takes the faulting instruction; ExcCode and EXL are set */
+        assert_eq!(cp0.exception(Exc::Sys, 0xFFFF_FFFF_8000_1000, false), VEC_BASE + VEC_GENERAL);
+        assert_eq!(cp0.rgpr(CP0_EPC), 0xFFFF_FFFF_8000_1000);
+        assert_eq!(cp0.rgpr(CP0_CAUSE) & CAUSE_BD, 0);
+        assert_eq!((cp0.rgpr(CP0_CAUSE) & CAUSE_EXCCODE) >> 2, 8);
+        assert_eq!(cp0.rgpr(CP0_STATUS) & STATUS_EXL, STATUS_EXL);
+
+        /* with EXL set, EPC and BD are left alone and refills use the general vector */
+        assert_eq!(cp0.exception(Exc::Tlbl(0x10, true), 0xFFFF_FFFF_8000_2004, true), VEC_BASE + VEC_GENERAL);
+        assert_eq!(cp0.rgpr(CP0_EPC), 0xFFFF_FFFF_8000_1000);
+        assert_eq!(cp0.rgpr(CP0_CAUSE) & CAUSE_BD, 0);
+        assert_eq!((cp0.rgpr(CP0_CAUSE) & CAUSE_EXCCODE) >> 2, 2);
+    }
+
+    #[test]
+    fn exception_delay_slot() {
+        let mut cp0 = CP0::new();
+
+        /* an exception in a delay slot restarts at the branch */
+        cp0.exception(Exc::Ov, 0xFFFF_FFFF_8000_1004, true);
+        assert_eq!(cp0.rgpr(CP0_EPC), 0xFFFF_FFFF_8000_1000);
+        assert_eq!(cp0.rgpr(CP0_CAUSE) & CAUSE_BD, CAUSE_BD);
+
+        /* BD is cleared by the next exception taken outside a delay slot */
+        cp0.wgpr(0, CP0_STATUS);
+        cp0.exception(Exc::Ov, 0xFFFF_FFFF_8000_2000, false);
+        assert_eq!(cp0.rgpr(CP0_EPC), 0xFFFF_FFFF_8000_2000);
+        assert_eq!(cp0.rgpr(CP0_CAUSE) & CAUSE_BD, 0);
+    }
+
+    #[test]
+    fn exception_bev_vectors() {
+        let mut cp0 = CP0::new();
+
+        cp0.wgpr(STATUS_BEV, CP0_STATUS);
+        assert_eq!(cp0.exception(Exc::Tlbl(0x10, true), 0, false), VEC_BASE_BEV + VEC_TLB_REFILL);
+        cp0.wgpr(STATUS_BEV, CP0_STATUS);
+        assert_eq!(cp0.exception(Exc::Sys, 0, false), VEC_BASE_BEV + VEC_GENERAL);
+    }
+
+    #[test]
+    fn exception_xtlb_vector() {
+        let mut cp0 = CP0::new();
+
+        /* refills use the XTLB vector when the faulting mode addresses 64 bits */
+        cp0.wgpr(STATUS_KX, CP0_STATUS);
+        assert_eq!(cp0.exception(Exc::Tlbl(0x10, true), 0, false), VEC_BASE + VEC_XTLB_REFILL);
+
+        cp0.wgpr(STATUS_UX | (2 << STATUS_KSU_SHIFT), CP0_STATUS);
+        assert_eq!(cp0.exception(Exc::Tlbs(0x10, true), 0, false), VEC_BASE + VEC_XTLB_REFILL);
+
+        cp0.wgpr(STATUS_KX | (2 << STATUS_KSU_SHIFT), CP0_STATUS);
+        assert_eq!(cp0.exception(Exc::Tlbl(0x10, true), 0, false), VEC_BASE + VEC_TLB_REFILL);
+    }
+
+    #[test]
+    fn index_wired_masks() {
+        let mut cp0 = CP0::new();
+
+        /* Index and Wired hold a 5-bit TLB entry number */
+        cp0.wgpr(0xFFFF_FFFF, CP0_INDEX);
+        assert_eq!(cp0.rgpr(CP0_INDEX), 0x1F);
+        cp0.wgpr(0x3F, CP0_WIRED);
+        assert_eq!(cp0.rgpr(CP0_WIRED), 0x1F);
+    }
+
+    #[test]
+    fn random_wraps_at_wired() {
+        let mut cp0 = CP0::new();
+        cp0.wgpr(28, CP0_WIRED);
+
+        /* Random counts down from the last entry to Wired, then wraps */
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            seen.push(cp0.rgpr(CP0_RANDOM));
+            cp0.tick();
+        }
+        assert_eq!(seen, [31, 30, 29, 28, 31]);
+
+        /* fixed, it only moves on TLBWR */
+        let mut cp0 = CP0::new();
+        cp0.set_fixed_random(true);
+        cp0.tick();
+        cp0.tick();
+        assert_eq!(cp0.rgpr(CP0_RANDOM), 31);
+        cp0.exec(Inst(0x4200_0006));
+        assert_eq!(cp0.rgpr(CP0_RANDOM), 30);
+    }
+
+    const USER: u64 = 2 << STATUS_KSU_SHIFT;
+    const SUPERVISOR: u64 = 1 << STATUS_KSU_SHIFT;
+
+    #[test]
+    fn user_address_errors() {
+        let mut cp0 = CP0::new();
+        cp0.wgpr(USER, CP0_STATUS);
+
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_8000_0000, false), Err(Exc::AdEL(0xFFFF_FFFF_8000_0000)));
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_A000_0000, true), Err(Exc::AdES(0xFFFF_FFFF_A000_0000)));
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_C000_0000, false), Err(Exc::AdEL(0xFFFF_FFFF_C000_0000)));
+        assert_eq!(cp0.vtop(0x0010_1000, false), Err(Exc::Tlbl(0x0010_1000, true)));
+
+        /* with 64-bit addressing, only xuseg is open to user mode */
+        cp0.wgpr(USER | STATUS_UX, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x4000_0000_0000_0000, false), Err(Exc::AdEL(0x4000_0000_0000_0000)));
+        assert_eq!(cp0.vtop(0x9000_0000_0000_0000, true), Err(Exc::AdES(0x9000_0000_0000_0000)));
+        assert_eq!(cp0.vtop(0xC000_0000_0000_0000, false), Err(Exc::AdEL(0xC000_0000_0000_0000)));
+        assert_eq!(cp0.vtop(0x0000_0100_0000_0000, false), Err(Exc::AdEL(0x0000_0100_0000_0000)));
+    }
+
+    #[test]
+    fn supervisor_address_errors() {
+        let mut cp0 = CP0::new();
+        cp0.wgpr(SUPERVISOR, CP0_STATUS);
+
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_8000_0000, false), Err(Exc::AdEL(0xFFFF_FFFF_8000_0000)));
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_E000_0000, true), Err(Exc::AdES(0xFFFF_FFFF_E000_0000)));
+        assert_eq!(cp0.vtop(0xFFFF_FFFF_C000_0000, false), Err(Exc::Tlbl(0xFFFF_FFFF_C000_0000, true)));
+
+        /* with 64-bit addressing, xsseg opens up but xkphys and xkseg stay closed */
+        cp0.wgpr(SUPERVISOR | STATUS_SX, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x4000_0000_0000_0000, false), Err(Exc::Tlbl(0x4000_0000_0000_0000, true)));
+        assert_eq!(cp0.vtop(0x9000_0000_0000_0000, true), Err(Exc::AdES(0x9000_0000_0000_0000)));
+        assert_eq!(cp0.vtop(0xC000_0000_0000_0000, false), Err(Exc::AdEL(0xC000_0000_0000_0000)));
+    }
+
+    #[test]
+    fn extended_addressing() {
+        let mut cp0 = CP0::new();
+
+        /* without KX, kernel addresses are truncated to 32 bits and sign-extended */
+        assert_eq!(cp0.vtop(0x9000_0000_0010_1000, false), Err(Exc::Tlbl(0x0010_1000, true)));
+        cp0.wgpr(STATUS_KX, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x9000_0000_0010_1000, false), Ok((0x0010_1000, false)));
+        assert_eq!(cp0.vtop(0xC000_0000_0000_0000, false), Err(Exc::Tlbl(0xC000_0000_0000_0000, true)));
+
+        /* SX and UX do the same for supervisor and user mode */
+        cp0.wgpr(SUPERVISOR, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x4000_0000_0010_1000, false), Err(Exc::Tlbl(0x0010_1000, true)));
+        cp0.wgpr(SUPERVISOR | STATUS_SX, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x4000_0000_0010_1000, false), Err(Exc::Tlbl(0x4000_0000_0010_1000, true)));
+
+        cp0.wgpr(USER, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x0000_0080_0010_1000, false), Err(Exc::Tlbl(0x0010_1000, true)));
+        cp0.wgpr(USER | STATUS_UX, CP0_STATUS);
+        assert_eq!(cp0.vtop(0x0000_0080_0010_1000, false), Err(Exc::Tlbl(0x0000_0080_0010_1000, true)));
+    }
+}
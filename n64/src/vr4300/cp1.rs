@@ -1,35 +1,734 @@
 /* cp1.rs - The CP1 (Co-Processor 1) module. */
 
+/*
+
+FCR31 (FCSR)
+
+  [1:0]   RM - rounding mode (0 = RN, 1 = RZ, 2 = RP, 3 = RM)
+  [6:2]   Flags - sticky exception flags (I, U, O, Z, V)
+  [11:7]  Enables - exception enables (I, U, O, Z, V)
+  [17:12] Cause - exceptions raised by the last instruction (I, U, O, Z, V, E)
+  [23]    C - condition bit
+  [24]    FS - flush denormalized results to zero
+
+FPR layout
+
+  Status.FR = 1: 32 64-bit registers.
+  Status.FR = 0: 16 64-bit registers (the even ones); 32-bit accesses to an
+                 odd register address the upper half of the even register below it.
+
+*/
+
 use super::*;
 
 const CP1_CONFIG: usize = 0x10;
 
+/* Values of the fmt field of CP1 arithmetic instructions. */
+pub const FMT_S: usize = 0x10;
+pub const FMT_D: usize = 0x11;
+pub const FMT_W: usize = 0x14;
+pub const FMT_L: usize = 0x15;
+
+/* CP1 control registers. */
+pub const FCR_REV: usize = 0;
+pub const FCR_CSR: usize = 31;
+
+/* Implementation and revision number reported by FCR0. */
+const FCR0_REV: u32 = 0x0000_0B00;
+
+/* FCR31 fields. */
+const FCSR_RM: u32 = 0x3;
+const FCSR_FLAGS_SHIFT: u32 = 2;
+const FCSR_ENABLES_SHIFT: u32 = 7;
+const FCSR_CAUSE_SHIFT: u32 = 12;
+const FCSR_CAUSE: u32 = 0x3F << FCSR_CAUSE_SHIFT;
+const FCSR_C: u32 = 1 << 23;
+const FCSR_FS: u32 = 1 << 24;
+const FCSR_MASK: u32 = 0x0183_FFFF;
+
+/* Floating-point exceptions, in the bit order of the flag, enable and cause fields. */
+const FPE_I: u32 = 1 << 0;
+const FPE_U: u32 = 1 << 1;
+const FPE_O: u32 = 1 << 2;
+const FPE_Z: u32 = 1 << 3;
+const FPE_V: u32 = 1 << 4;
+const FPE_E: u32 = 1 << 5;
+
+/* Rounding modes. */
+const RM_RN: u32 = 0;
+const RM_RZ: u32 = 1;
+const RM_RP: u32 = 2;
+const RM_RM: u32 = 3;
+
+/* Quiet NaNs produced by invalid operations. */
+const NAN_S: u32 = 0x7FBF_FFFF;
+const NAN_D: u64 = 0x7FF7_FFFF_FFFF_FFFF;
+
+/* A result rounded to nearest in double precision, along with its rounding error (exact - r). */
+#[derive(Copy, Clone)]
+struct Exact {
+    r: f64,
+    e: f64
+}
+
 pub struct CP1 {
-    /* the 32-bit cop0 general purpose registers */
-    regs: [u32; GPR_SIZE],
+    /* the 64-bit floating-point general purpose registers */
+    regs: [u64; GPR_SIZE],
+
+    /* control/status register */
+    fcr31: u32,
+
+    /* mirrors Status.FR, selecting the 32-register layout */
+    pub fr: bool
 }
 
 impl CP1 {
 
     pub fn new() -> CP1 {
         CP1 {
-            /* zero-initialize the cop0 registers */
-            regs: [0; GPR_SIZE]
+            /* zero-initialize the cop1 registers */
+            regs: [0; GPR_SIZE],
+
+            fcr31: 0,
+
+            fr: false
         }
     }
 
-    /* reads from a cop0 register */
+    /* reads from a cop1 register */
     pub fn rfpr(&self, reg: usize) -> u64 {
-        self.regs[reg] as u64
+        self.regs[reg]
     }
 
-    /* writes to a cop0 register */
+    /* writes to a cop1 register */
     pub fn wfpr(&mut self, val: u64, reg: usize) {
-        self.regs[reg] = val as u32;
+        self.regs[reg] = val;
+    }
+
+    /* reads from a cop1 control register */
+    pub fn rfcr(&self, reg: usize) -> u32 {
+        match reg {
+            FCR_REV => FCR0_REV,
+            FCR_CSR => self.fcr31,
+            _ => 0
+        }
+    }
+
+    /* writes to a cop1 control register */
+    pub fn wfcr(&mut self, val: u32, reg: usize) {
+        if reg == FCR_CSR {
+            self.fcr31 = val & FCSR_MASK;
+        }
+    }
+
+    /* reads 32 bits from a cop1 register according to the register layout */
+    pub fn r32(&self, reg: usize) -> u32 {
+        if self.fr || (reg & 1) == 0 {
+            self.regs[reg] as u32
+        } else {
+            (self.regs[reg & !1] >> 32) as u32
+        }
+    }
+
+    /* writes 32 bits to a cop1 register according to the register layout */
+    pub fn w32(&mut self, val: u32, reg: usize) {
+        if self.fr || (reg & 1) == 0 {
+            self.regs[reg] = (self.regs[reg] & 0xFFFF_FFFF_0000_0000) | val as u64;
+        } else {
+            let reg = reg & !1;
+            self.regs[reg] = (self.regs[reg] & 0x0000_0000_FFFF_FFFF) | ((val as u64) << 32);
+        }
+    }
+
+    /* reads 64 bits from a cop1 register according to the register layout */
+    pub fn r64(&self, reg: usize) -> u64 {
+        if self.fr { self.regs[reg] } else { self.regs[reg & !1] }
+    }
+
+    /* writes 64 bits to a cop1 register according to the register layout */
+    pub fn w64(&mut self, val: u64, reg: usize) {
+        if self.fr { self.regs[reg] = val } else { self.regs[reg & !1] = val }
+    }
+
+    /* reads a single-precision value from a cop1 register */
+    fn rs(&self, reg: usize) -> f32 {
+        f32::from_bits(self.r32(reg))
+    }
+
+    /* writes a single-precision value to a cop1 register */
+    fn ws(&mut self, val: f32, reg: usize) {
+        self.w32(val.to_bits(), reg);
+    }
+
+    /* reads a double-precision value from a cop1 register */
+    fn rd(&self, reg: usize) -> f64 {
+        f64::from_bits(self.r64(reg))
+    }
+
+    /* writes a double-precision value to a cop1 register */
+    fn wd(&mut self, val: f64, reg: usize) {
+        self.w64(val.to_bits(), reg);
+    }
+
+    /* the condition bit set by the compare instructions */
+    pub fn c(&self) -> bool {
+        (self.fcr31 & FCSR_C) != 0
+    }
+
+    /* reads a cop1 register for MFC1, DMFC1 or CFC1 */
+    pub fn rmov(&self, op: Op, reg: usize) -> u64 {
+        match op {
+            Op::Mf => self.r32(reg) as i32 as u64,
+            Op::Dmf => self.r64(reg),
+            _ => self.rfcr(reg) as i32 as u64
+        }
+    }
+
+    /* writes a cop1 register for MTC1, DMTC1 or CTC1 */
+    pub fn wmov(&mut self, op: Op, val: u64, reg: usize) -> Result<(), Exc> {
+        match op {
+            Op::Mt => self.w32(val as u32, reg),
+            Op::Dmt => self.w64(val, reg),
+            _ => {
+                self.wfcr(val as u32, reg);
+
+                /* setting a cause bit whose exception is enabled traps immediately */
+                let cause = (self.fcr31 & FCSR_CAUSE) >> FCSR_CAUSE_SHIFT;
+                let enables = ((self.fcr31 >> FCSR_ENABLES_SHIFT) & 0x1F) | FPE_E;
+                if reg == FCR_CSR && (cause & enables) != 0 {
+                    return Err(Exc::Fpe);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /* the current rounding mode */
+    fn rm(&self) -> u32 {
+        self.fcr31 & FCSR_RM
+    }
+
+    /* NaNs and denormalized operands are not handled by the hardware. */
+    fn unimplemented(x: f64, single: bool) -> bool {
+        let min = if single { f32::MIN_POSITIVE as f64 } else { f64::MIN_POSITIVE };
+        x.is_nan() || (x != 0.0 && x.abs() < min)
+    }
+
+    /* Adds two values, keeping the rounding error. */
+    fn add(&self, a: f64, b: f64) -> Exact {
+        let r = a + b;
+
+        /* an exactly zero sum of opposite signs is -0 only when rounding toward minus infinity */
+        if r == 0.0 {
+            let neg = if a == 0.0 && b == 0.0 && a.is_sign_negative() == b.is_sign_negative() {
+                a.is_sign_negative()
+            } else {
+                self.rm() == RM_RM
+            };
+            return Exact { r: if neg { -0.0 } else { 0.0 }, e: 0.0 };
+        }
+
+        if !r.is_finite() {
+            return Exact { r, e: 0.0 };
+        }
+
+        let bb = r - a;
+        Exact { r, e: (a - (r - bb)) + (b - bb) }
+    }
+
+    /* Multiplies two values, keeping the rounding error. */
+    fn mul(a: f64, b: f64) -> Exact {
+        let r = a * b;
+        let e = if r.is_finite() { a.mul_add(b, -r) } else { 0.0 };
+        Exact { r, e }
+    }
+
+    /* Divides two values, keeping the sign of the rounding error. */
+    fn div(a: f64, b: f64) -> Exact {
+        let r = a / b;
+        let e = if r.is_finite() && b != 0.0 { (-r).mul_add(b, a) * b.signum() } else { 0.0 };
+        Exact { r, e }
+    }
+
+    /* Calculates a square root, keeping the sign of the rounding error. */
+    fn sqrt(a: f64) -> Exact {
+        let r = a.sqrt();
+        let e = if r.is_finite() { (-r).mul_add(r, a) } else { 0.0 };
+        Exact { r, e }
+    }
+
+    /* Moves a value rounded to nearest one unit in the last place in the direction of the rounding mode. */
+    fn direct(&self, r: f64, e: f64, up: f64, down: f64) -> f64 {
+        match self.rm() {
+            RM_RZ if (e < 0.0 && r > 0.0) || (e > 0.0 && r < 0.0) => {
+                if r > 0.0 { down } else { up }
+            }, RM_RP if e > 0.0 => {
+                up
+            }, RM_RM if e < 0.0 => {
+                down
+            }, _ => r
+        }
+    }
+
+    /* Returns the result of an overflow according to the rounding mode. */
+    fn overflow(&self, neg: bool, max: f64, cause: &mut u32) -> f64 {
+        *cause |= FPE_O | FPE_I;
+
+        let inf = match self.rm() {
+            RM_RN => true,
+            RM_RZ => false,
+            RM_RP => !neg,
+            _ => neg
+        };
+        let r = if inf { f64::INFINITY } else { max };
+        if neg { -r } else { r }
+    }
+
+    /* Returns the result of flushing a denormalized value according to the rounding mode. */
+    fn flush(&self, neg: bool, min: f64) -> f64 {
+        let r = match self.rm() {
+            RM_RP if !neg => min,
+            RM_RM if neg => min,
+            _ => 0.0
+        };
+        if neg { -r } else { r }
+    }
+
+    /* Rounds an exact result to double precision, accumulating the exceptions it raises. */
+    fn round_d(&self, x: Exact, cause: &mut u32) -> f64 {
+
+        if x.r.is_nan() || x.r.is_infinite() {
+            return x.r;
+        }
+
+        let mut r = x.r;
+
+        if x.e != 0.0 {
+            *cause |= FPE_I;
+            r = self.direct(r, x.e, r.next_up(), r.next_down());
+        }
+
+        if r.is_infinite() {
+            r = self.overflow(x.r.is_sign_negative(), f64::MAX, cause);
+        } else if r.is_subnormal() || (r == 0.0 && x.e != 0.0) {
+            r = self.tiny(r, x.e, f64::MIN_POSITIVE, cause);
+        }
+
+        r
+    }
+
+    /* Rounds an exact result to single precision, accumulating the exceptions it raises. */
+    fn round_s(&self, x: Exact, cause: &mut u32) -> f32 {
+
+        if x.r.is_nan() || x.r.is_infinite() {
+            return x.r as f32;
+        }
+
+        let n = x.r as f32;
+
+        if n.is_infinite() {
+            return self.overflow(x.r.is_sign_negative(), f32::MAX as f64, cause) as f32;
+        }
+
+        /* the error of the single-precision result is dominated by the conversion, if any */
+        let e = if n as f64 != x.r { x.r - n as f64 } else { x.e };
+
+        let mut r = n;
+
+        if e != 0.0 {
+            *cause |= FPE_I;
+            r = self.direct(n as f64, e, n.next_up() as f64, n.next_down() as f64) as f32;
+        }
+
+        if r.is_infinite() {
+            r = self.overflow(x.r.is_sign_negative(), f32::MAX as f64, cause) as f32;
+        } else if r.is_subnormal() || (r == 0.0 && e != 0.0) {
+            r = self.tiny(r as f64, e, f32::MIN_POSITIVE as f64, cause) as f32;
+        }
+
+        r
     }
 
-    pub fn exec(&mut self, i: Inst) {
-        unimplemented!();
+    /* Handles a result too small to be normalized, either flushing it or leaving it to software. */
+    fn tiny(&self, r: f64, e: f64, min: f64, cause: &mut u32) -> f64 {
+        if (self.fcr31 & FCSR_FS) != 0 {
+            *cause |= FPE_U | FPE_I;
+            self.flush(r.is_sign_negative() || (r == 0.0 && e < 0.0), min)
+        } else {
+            *cause |= FPE_E;
+            r
+        }
     }
 
+    /* Rounds a value to an integer using the given rounding mode. */
+    fn rint(x: f64, rm: u32) -> f64 {
+        match rm {
+            RM_RN => x.round_ties_even(),
+            RM_RZ => x.trunc(),
+            RM_RP => x.ceil(),
+            _ => x.floor()
+        }
+    }
+
+    /* Reads an operand of the given format as a double. */
+    fn operand(&self, fmt: usize, reg: usize) -> f64 {
+        match fmt {
+            FMT_S => self.rs(reg) as f64,
+            _ => self.rd(reg)
+        }
+    }
+
+    /* Updates the cause field and either commits the flags or traps. */
+    fn raise(&mut self, cause: u32) -> Result<(), Exc> {
+        let enables = (self.fcr31 >> FCSR_ENABLES_SHIFT) & 0x1F;
+
+        self.fcr31 = (self.fcr31 & !FCSR_CAUSE) | (cause << FCSR_CAUSE_SHIFT);
+
+        /* unimplemented operations always trap; the rest trap when enabled */
+        if (cause & (enables | FPE_E)) != 0 {
+            return Err(Exc::Fpe);
+        }
+
+        self.fcr31 |= (cause & 0x1F) << FCSR_FLAGS_SHIFT;
+
+        Ok(())
+    }
+
+    /* Executes an arithmetic instruction in the S or D format. */
+    fn arith(&mut self, i: Inst) -> Result<(), Exc> {
+
+        let fmt = i.fmt();
+        let single = fmt == FMT_S;
+
+        if fmt != FMT_S && fmt != FMT_D {
+            return self.raise(FPE_E);
+        }
+
+        /* mov is a plain copy and never traps */
+        if let Op::Fmov = i.op() {
+            if single {
+                let val = self.r32(i.fs());
+                self.w32(val, i.fd());
+            } else {
+                let val = self.r64(i.fs());
+                self.w64(val, i.fd());
+            }
+            return Ok(());
+        }
+
+        let (a, b) = (self.operand(fmt, i.fs()), self.operand(fmt, i.ft()));
+
+        let binary = matches!(i.op(), Op::Fadd | Op::Fsub | Op::Fmul | Op::Fdiv);
+
+        if Self::unimplemented(a, single) || (binary && Self::unimplemented(b, single)) {
+            return self.raise(FPE_E);
+        }
+
+        let mut cause = 0;
+
+        let x = match i.op() {
+            Op::Fadd => self.add(a, b),
+            Op::Fsub => self.add(a, -b),
+            Op::Fmul => Self::mul(a, b),
+            Op::Fdiv => {
+                if b == 0.0 && a != 0.0 && a.is_finite() {
+                    cause |= FPE_Z;
+                }
+                Self::div(a, b)
+            }, Op::Fsqrt => Self::sqrt(a),
+            Op::Fabs => Exact { r: a.abs(), e: 0.0 },
+            _ => Exact { r: -a, e: 0.0 }
+        };
+
+        if x.r.is_nan() {
+            cause |= FPE_V;
+        }
+
+        /* an infinite result from finite operands is an overflow rather than an exact infinity */
+        let overflow = x.r.is_infinite() && a.is_finite() && (!binary || b.is_finite()) && (cause & FPE_Z) == 0;
+
+        if single {
+            let r = if x.r.is_nan() {
+                f32::from_bits(NAN_S)
+            } else if overflow {
+                self.overflow(x.r < 0.0, f32::MAX as f64, &mut cause) as f32
+            } else {
+                self.round_s(x, &mut cause)
+            };
+            self.raise(cause)?;
+            self.ws(r, i.fd());
+        } else {
+            let r = if x.r.is_nan() {
+                f64::from_bits(NAN_D)
+            } else if overflow {
+                self.overflow(x.r < 0.0, f64::MAX, &mut cause)
+            } else {
+                self.round_d(x, &mut cause)
+            };
+            self.raise(cause)?;
+            self.wd(r, i.fd());
+        }
+
+        Ok(())
+    }
+
+    /* Executes a conversion between formats. */
+    fn convert(&mut self, i: Inst) -> Result<(), Exc> {
+
+        let fmt = i.fmt();
+        let mut cause = 0;
+
+        /* read the source as an exact value */
+        let src = match fmt {
+            FMT_S | FMT_D => {
+                let x = self.operand(fmt, i.fs());
+                if Self::unimplemented(x, fmt == FMT_S) {
+                    return self.raise(FPE_E);
+                }
+                Exact { r: x, e: 0.0 }
+            }, FMT_W => {
+                Exact { r: self.r32(i.fs()) as i32 as f64, e: 0.0 }
+            }, FMT_L => {
+                let l = self.r64(i.fs()) as i64;
+                let r = l as f64;
+                Exact { r, e: (l as i128 - r as i128) as f64 }
+            }, _ => {
+                return self.raise(FPE_E);
+            }
+        };
+
+        /* the rounding mode used for conversions to integers */
+        let rm = match i.op() {
+            Op::FroundL | Op::FroundW => RM_RN,
+            Op::FtruncL | Op::FtruncW => RM_RZ,
+            Op::FceilL | Op::FceilW => RM_RP,
+            Op::FfloorL | Op::FfloorW => RM_RM,
+            _ => self.rm()
+        };
+
+        match i.op() {
+            Op::Fcvts if fmt != FMT_S => {
+                /* longs are rounded straight to single precision to avoid rounding twice */
+                let src = if fmt == FMT_L {
+                    let l = self.r64(i.fs()) as i64;
+                    let n = l as f32 as f64;
+                    Exact { r: n, e: (l as i128 - n as i128) as f64 }
+                } else {
+                    src
+                };
+                let r = self.round_s(src, &mut cause);
+                self.raise(cause)?;
+                self.ws(r, i.fd());
+            }, Op::Fcvtd if fmt != FMT_D => {
+                let r = self.round_d(src, &mut cause);
+                self.raise(cause)?;
+                self.wd(r, i.fd());
+            }, Op::Fcvtw | Op::FroundW | Op::FtruncW | Op::FceilW | Op::FfloorW if fmt == FMT_S || fmt == FMT_D => {
+                let r = Self::rint(src.r, rm);
+                /* values that do not fit the integer format are invalid, and left to software */
+                if !(-2147483648.0..=2147483647.0).contains(&r) {
+                    return self.raise(FPE_E | FPE_V);
+                }
+                if r != src.r {
+                    cause |= FPE_I;
+                }
+                self.raise(cause)?;
+                self.w32(r as i32 as u32, i.fd());
+            }, Op::Fcvtl | Op::FroundL | Op::FtruncL | Op::FceilL | Op::FfloorL if fmt == FMT_S || fmt == FMT_D => {
+                let r = Self::rint(src.r, rm);
+                if !(-9223372036854775808.0..9223372036854775808.0).contains(&r) {
+                    return self.raise(FPE_E | FPE_V);
+                }
+                if r != src.r {
+                    cause |= FPE_I;
+                }
+                self.raise(cause)?;
+                self.w64(r as i64 as u64, i.fd());
+            }, _ => {
+                return self.raise(FPE_E);
+            }
+        }
+
+        Ok(())
+    }
+
+    /* Determines whether an operand is a signaling NaN (quiet bit set, in the MIPS encoding). */
+    fn snan(&self, fmt: usize, reg: usize) -> bool {
+        if fmt == FMT_S {
+            let x = self.r32(reg);
+            f32::from_bits(x).is_nan() && (x & (1 << 22)) != 0
+        } else {
+            let x = self.r64(reg);
+            f64::from_bits(x).is_nan() && (x & (1 << 51)) != 0
+        }
+    }
+
+    /* Executes a C.cond.fmt compare, setting the condition bit. */
+    fn compare(&mut self, i: Inst) -> Result<(), Exc> {
+
+        let fmt = i.fmt();
+
+        if fmt != FMT_S && fmt != FMT_D {
+            return self.raise(FPE_E);
+        }
+
+        let (a, b) = (self.operand(fmt, i.fs()), self.operand(fmt, i.ft()));
+
+        /* cond: [3] signal on unordered, [2] less than, [1] equal, [0] unordered */
+        let cond = i.funct() & 0xF;
+        let unordered = a.is_nan() || b.is_nan();
+
+        let mut cause = 0;
+        if unordered && ((cond & 0x8) != 0 || self.snan(fmt, i.fs()) || self.snan(fmt, i.ft())) {
+            cause |= FPE_V;
+        }
+
+        let c = ((cond & 0x1) != 0 && unordered) ||
+            ((cond & 0x2) != 0 && a == b) ||
+            ((cond & 0x4) != 0 && a < b);
+
+        self.raise(cause)?;
+
+        if c {
+            self.fcr31 |= FCSR_C;
+        } else {
+            self.fcr31 &= !FCSR_C;
+        }
+
+        Ok(())
+    }
+
+    pub fn exec(&mut self, i: Inst) -> Result<(), Exc> {
+
+        match i.op() {
+            Op::Fadd | Op::Fsub | Op::Fmul | Op::Fdiv | Op::Fsqrt | Op::Fabs | Op::Fmov | Op::Fneg => {
+                self.arith(i)
+            }, Op::FroundL | Op::FtruncL | Op::FceilL | Op::FfloorL |
+               Op::FroundW | Op::FtruncW | Op::FceilW | Op::FfloorW |
+               Op::Fcvts | Op::Fcvtd | Op::Fcvtw | Op::Fcvtl => {
+                self.convert(i)
+            }, Op::Fcf | Op::Fcun | Op::Fceq | Op::Fcueq | Op::Fcolt | Op::Fcult | Op::Fcole | Op::Fcule |
+               Op::Fcsf | Op::Fcngle | Op::Fcseq | Op::Fcngl | Op::Fclt | Op::Fcnge | Op::Fcle | Op::Fcngt => {
+                self.compare(i)
+            }, _ => {
+                /* undefined operations are left to software as unimplemented operations */
+                self.raise(FPE_E)
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Encodes a COP1 arithmetic instruction. */
+    fn inst(fmt: usize, ft: usize, fs: usize, fd: usize, funct: u32) -> Inst {
+        Inst((0x11 << 26) | ((fmt as u32) << 21) | ((ft as u32) << 16) | ((fs as u32) << 11) | ((fd as u32) << 6) | funct)
+    }
+
+    const ADD: u32 = 0x00;
+    const MUL: u32 = 0x02;
+    const DIV: u32 = 0x03;
+    const TRUNC_W: u32 = 0x0D;
+    const CVT_W: u32 = 0x24;
+
+    /* Executes a single-precision operation on a and b in the given rounding mode, returning the result and cause. */
+    fn exec_s(funct: u32, fcsr: u32, a: f32, b: f32) -> (Result<(), Exc>, u32, u32) {
+        let mut cp1 = CP1::new();
+        cp1.wfcr(fcsr, FCR_CSR);
+        cp1.w32(a.to_bits(), 2);
+        cp1.w32(b.to_bits(), 4);
+
+        let r = cp1.exec(inst(FMT_S, 4, 2, 0, funct));
+        (r, cp1.r32(0), (cp1.rfcr(FCR_CSR) & FCSR_CAUSE) >> FCSR_CAUSE_SHIFT)
+    }
+
+    #[test]
+    fn rounding_modes() {
+        /* 1/3 lies between 0x3EAAAAAA and 0x3EAAAAAB, nearer the latter */
+        let expect = [(RM_RN, 0x3EAA_AAAB, 0xBEAA_AAAB),
+                      (RM_RZ, 0x3EAA_AAAA, 0xBEAA_AAAA),
+                      (RM_RP, 0x3EAA_AAAB, 0xBEAA_AAAA),
+                      (RM_RM, 0x3EAA_AAAA, 0xBEAA_AAAB)];
+
+        for &(rm, pos, neg) in expect.iter() {
+            let (r, val, cause) = exec_s(DIV, rm, 1.0, 3.0);
+            assert_eq!(r, Ok(()));
+            assert_eq!(val, pos, "1/3 in mode {}", rm);
+            assert_eq!(cause, FPE_I);
+
+            let (_, val, _) = exec_s(DIV, rm, -1.0, 3.0);
+            assert_eq!(val, neg, "-1/3 in mode {}", rm);
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let inf = f32::INFINITY.to_bits();
+        let max = f32::MAX.to_bits();
+        let sign = 0x8000_0000;
+
+        let expect = [(RM_RN, inf, inf | sign),
+                      (RM_RZ, max, max | sign),
+                      (RM_RP, inf, max | sign),
+                      (RM_RM, max, inf | sign)];
+
+        for &(rm, pos, neg) in expect.iter() {
+            let (r, val, cause) = exec_s(MUL, rm, f32::MAX, 2.0);
+            assert_eq!(r, Ok(()));
+            assert_eq!(val, pos, "MAX*2 in mode {}", rm);
+            assert_eq!(cause, FPE_O | FPE_I);
+
+            let (_, val, _) = exec_s(MUL, rm, -f32::MAX, 2.0);
+            assert_eq!(val, neg, "-MAX*2 in mode {}", rm);
+        }
+    }
+
+    #[test]
+    fn denormal_input() {
+        let denormal = f32::from_bits(0x0000_0001);
+        let (r, val, cause) = exec_s(ADD, RM_RN, denormal, 1.0);
+
+        assert_eq!(r, Err(Exc::Fpe));
+        assert_eq!(cause, FPE_E);
+        /* the destination is left alone */
+        assert_eq!(val, 0);
+    }
+
+    #[test]
+    fn denormal_output() {
+        let (r, _, cause) = exec_s(MUL, RM_RN, f32::MIN_POSITIVE, 0.5);
+        assert_eq!(r, Err(Exc::Fpe));
+        assert_eq!(cause, FPE_E);
+
+        /* with FS set, tiny results are flushed instead */
+        let (r, val, cause) = exec_s(MUL, RM_RN | FCSR_FS, f32::MIN_POSITIVE, 0.5);
+        assert_eq!(r, Ok(()));
+        assert_eq!(val, 0);
+        assert_eq!(cause, FPE_U | FPE_I);
+
+        let (_, val, _) = exec_s(MUL, RM_RP | FCSR_FS, f32::MIN_POSITIVE, 0.5);
+        assert_eq!(val, f32::MIN_POSITIVE.to_bits());
+    }
+
+    #[test]
+    fn conversion_out_of_range() {
+        for &funct in [CVT_W, TRUNC_W].iter() {
+            let (r, _, cause) = exec_s(funct, RM_RN, 3.0e9, 0.0);
+            assert_eq!(r, Err(Exc::Fpe));
+            assert_eq!(cause, FPE_E | FPE_V);
+
+            let (r, _, cause) = exec_s(funct, RM_RN, f32::NEG_INFINITY, 0.0);
+            assert_eq!(r, Err(Exc::Fpe));
+            assert_eq!(cause, FPE_E | FPE_V);
+        }
+
+        /* in range, the conversion is only inexact */
+        let (r, val, cause) = exec_s(TRUNC_W, RM_RN, -2.5, 0.0);
+        assert_eq!(r, Ok(()));
+        assert_eq!(val as i32, -2);
+        assert_eq!(cause, FPE_I);
+    }
 }
@@ -0,0 +1,94 @@
+/* exception.rs - The sources of VR4300 exceptions and their CP0 encodings. */
+
+/*
+
+Cause.ExcCode
+
+  00h = Int     04h = AdEL    08h = Sys     0Ch = Ov      17h = WATCH
+  01h = Mod     05h = AdES    09h = Bp      0Dh = Tr
+  02h = TLBL    06h = IBE     0Ah = RI      0Eh = *RESERVED*
+  03h = TLBS    07h = DBE     0Bh = CpU     0Fh = FPE
+
+*/
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Exc {
+    /* interrupt */
+    Int,
+    /* TLB modification (faulting address) */
+    Mod(u64),
+    /* TLB miss or invalid on a load or fetch (faulting address, refill) */
+    Tlbl(u64, bool),
+    /* TLB miss or invalid on a store (faulting address, refill) */
+    Tlbs(u64, bool),
+    /* address error on a load or fetch (faulting address) */
+    AdEL(u64),
+    /* address error on a store (faulting address) */
+    AdES(u64),
+    /* bus error on a fetch */
+    Ibe,
+    /* bus error on a load or store */
+    Dbe,
+    /* syscall instruction */
+    Sys,
+    /* break instruction */
+    Bp,
+    /* reserved instruction */
+    RI,
+    /* coprocessor unusable (coprocessor number) */
+    CpU(u8),
+    /* integer overflow */
+    Ov,
+    /* trap instruction */
+    Tr,
+    /* floating-point exception */
+    Fpe,
+    /* watchpoint reference */
+    Watch
+}
+
+impl Exc {
+
+    /* Returns the code stored to the ExcCode field of the Cause register. */
+    pub fn code(&self) -> u64 {
+        match *self {
+            Exc::Int => 0x00,
+            Exc::Mod(_) => 0x01,
+            Exc::Tlbl(..) => 0x02,
+            Exc::Tlbs(..) => 0x03,
+            Exc::AdEL(_) => 0x04,
+            Exc::AdES(_) => 0x05,
+            Exc::Ibe => 0x06,
+            Exc::Dbe => 0x07,
+            Exc::Sys => 0x08,
+            Exc::Bp => 0x09,
+            Exc::RI => 0x0A,
+            Exc::CpU(_) => 0x0B,
+            Exc::Ov => 0x0C,
+            Exc::Tr => 0x0D,
+            Exc::Fpe => 0x0F,
+            Exc::Watch => 0x17
+        }
+    }
+
+    /* Returns the virtual address that caused an address or TLB exception. */
+    pub fn vaddr(&self) -> Option<u64> {
+        match *self {
+            Exc::Mod(a) | Exc::Tlbl(a, _) | Exc::Tlbs(a, _) | Exc::AdEL(a) | Exc::AdES(a) => Some(a),
+            _ => None
+        }
+    }
+
+    /* Determines whether the exception is a TLB exception. */
+    pub fn tlb(&self) -> bool {
+        matches!(*self, Exc::Mod(_) | Exc::Tlbl(..) | Exc::Tlbs(..))
+    }
+
+    /* Determines whether the exception is a TLB refill, which has its own vector. */
+    pub fn refill(&self) -> bool {
+        match *self {
+            Exc::Tlbl(_, r) | Exc::Tlbs(_, r) => r,
+            _ => false
+        }
+    }
+}
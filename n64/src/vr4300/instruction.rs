@@ -40,9 +40,16 @@ impl Inst {
                     }, _ => t
                 }
             }, Op::Cop1 => {
-                panic!("Attempt to resolve Cop1 instruction.")
-            }, Op::Cop2 => {
-                panic!("Attempt to resolve Cop2 instruction.")
+                /* If the fmt field selects a format, the instruction is an arithmetic instruction from the FN table. */
+                if self.fmt() >= FMT_S {
+                    return CP1_OP_FN_TABLE[((self.funct() >> 3) & 0b111) as usize][(self.funct() & 0b111) as usize];
+                }
+                let t = COP_OP_RS_TABLE[((self._rs() >> 3) & 0b11) as usize][(self._rs() & 0b111) as usize];
+                match t.0 {
+                    Op::Bc => {
+                        return COP_OP_RT_TABLE[((self._rt() >> 3) & 0b11) as usize][(self._rt() & 0b111) as usize];
+                    }, _ => t
+                }
             }, _ => t,
         }
     }
@@ -60,13 +67,26 @@ impl Inst {
     /* Returns a string of the opcode for debugging. */
     pub fn op_str(&self) -> String {
         let s = self.op_tup().1;
+
+        /* coprocessor branches put the coprocessor number after the bc, e.g. bc1t */
+        let cop = |z: u32| match self.op() {
+            Op::Bcf | Op::Bct | Op::Bcfl | Op::Bctl => format!("bc{}{}", z, &s[2..]),
+            _ => format!("{}c{}", s, z)
+        };
+
         match self.kind() {
             Op::Cop0 => {
-                format!("{}c0", s)
+                cop(0)
             }, Op::Cop1 => {
-                format!("{}c1", s)
+                match self.fmt() {
+                    FMT_S => format!("{}.s", s),
+                    FMT_D => format!("{}.d", s),
+                    FMT_W => format!("{}.w", s),
+                    FMT_L => format!("{}.l", s),
+                    _ => cop(1)
+                }
             }, Op::Cop2 => {
-                format!("{}c2", s)
+                cop(2)
             }, _ => s.to_owned()
         }
     }
@@ -116,6 +136,67 @@ impl Inst {
         return (self.0 & 0xffff) as u16;
     }
 
+    /* Returns the number of bytes transferred by a LD/ST instruction. */
+    pub fn size(&self) -> usize {
+        match self.op() {
+            Op::Lb | Op::Lbu | Op::Sb => 1,
+            Op::Lh | Op::Lhu | Op::Sh => 2,
+            Op::Ld | Op::Sd | Op::Lld | Op::Scd | Op::Ldc1 | Op::Sdc1 |
+            Op::Ldl | Op::Ldr | Op::Sdl | Op::Sdr => 8,
+            _ => 4
+        }
+    }
+
+    /* Returns the coprocessor an instruction uses, if any. */
+    pub fn cop(&self) -> Option<u8> {
+        match (self.kind(), self.op()) {
+            (Op::Cop0, _) | (_, Op::Cache) => Some(0),
+            (Op::Cop1, _) | (_, Op::Lwc1) | (_, Op::Ldc1) | (_, Op::Swc1) | (_, Op::Sdc1) => Some(1),
+            (Op::Cop2, _) | (_, Op::Lwc2) | (_, Op::Ldc2) | (_, Op::Swc2) | (_, Op::Sdc2) => Some(2),
+            _ => None
+        }
+    }
+
+    /* Determines whether the instruction is a branch likely, which discards its delay slot when not taken. */
+    pub fn likely(&self) -> bool {
+        matches!(self.op(),
+            Op::Beql | Op::Bnel | Op::Blezl | Op::Bgtzl |
+            Op::Bltzl | Op::Bgezl | Op::Bltzall | Op::Bgezall |
+            Op::Bcfl | Op::Bctl)
+    }
+
+    /* Determines whether the instruction is a conditional trap, which has no result to write back. */
+    pub fn trap(&self) -> bool {
+        matches!(self.op(),
+            Op::Tge | Op::Tgeu | Op::Tlt | Op::Tltu | Op::Teq | Op::Tne |
+            Op::Tgei | Op::Tgeiu | Op::Tlti | Op::Tltiu | Op::Teqi | Op::Tnei)
+    }
+
+    /* Determines whether a LD/ST instruction merges part of a word or doubleword with a register or memory. */
+    pub fn merges(&self) -> bool {
+        matches!(self.op(), Op::Lwl | Op::Lwr | Op::Ldl | Op::Ldr | Op::Swl | Op::Swr | Op::Sdl | Op::Sdr)
+    }
+
+    /* Returns the format field of a CP1 instruction. */
+    pub fn fmt(&self) -> usize {
+        self._rs()
+    }
+
+    /* Returns the source floating-point register of a CP1 instruction. */
+    pub fn fs(&self) -> usize {
+        self._rd()
+    }
+
+    /* Returns the target floating-point register of a CP1 instruction. */
+    pub fn ft(&self) -> usize {
+        self._rt()
+    }
+
+    /* Returns the destination floating-point register of a CP1 instruction. */
+    pub fn fd(&self) -> usize {
+        self.sa() as usize
+    }
+
     /* Returns the function's target. */
     pub fn target(&self) -> u64 {
         return (self.0 & 0x3ffffff) as u64;
@@ -125,7 +206,15 @@ impl Inst {
 impl fmt::Display for Inst {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind() {
-            Op::Cop0 | Op::Cop1 | Op::Cop2 => {
+            Op::Cop1 if self.fmt() >= FMT_S && self.funct() >= 0x30 => {
+                write!(f, "{} f{}, f{}", self.op_str(), self.fs(), self.ft())
+            }, Op::Cop1 if self.fmt() >= FMT_S => {
+                write!(f, "{} f{}, f{}, f{}", self.op_str(), self.fd(), self.fs(), self.ft())
+            }, Op::Cop0 | Op::Cop1 | Op::Cop2 if self._rs() == 0x08 => {
+                write!(f, "{} {}", self.op_str(), (self.offset() as i16 as i32) << 2)
+            }, Op::Cop1 => {
+                write!(f, "{} {}, f{}", self.op_str(), GPR_NAMES[self._rt()], self.fs())
+            }, Op::Cop0 | Op::Cop2 => {
                 write!(f, "{} {}, {}", self.op_str(), GPR_NAMES[self._rt()],  CP0_NAMES[self._rd()])
             }, _ => {
                 match self.class() {
@@ -138,7 +227,7 @@ impl fmt::Display for Inst {
                         match self.op() {
 
                             Op::J | Op::Jal  => {
-                                write!(f, "{} {:#x}", self.op_str(), self.target())
+                                write!(f, "{} {:#x}", self.op_str(), self.target() << 2)
                             }, Op::Jr | Op::Jalr => {
                                 write!(f, "{} {}", self.op_str(),GPR_NAMES[self._rs()])
                             }, _ => {
@@ -149,7 +238,17 @@ impl fmt::Display for Inst {
                     }, OpC::B => {
                         write!(f, "{} {}, {}, {}", self.op_str(), GPR_NAMES[self._rs()], GPR_NAMES[self._rt()], (self.offset() as i16 as i32) << 2)
                     }, OpC::R => {
-                        write!(f, "{} {}, {}, {}", self.op_str(), GPR_NAMES[self._rd()], GPR_NAMES[self._rs()], GPR_NAMES[self._rt()])
+                        match self.op() {
+                            Op::Mult | Op::Multu | Op::Div | Op::Divu | Op::Dmult | Op::Dmultu | Op::Ddiv | Op::Ddivu => {
+                                write!(f, "{} {}, {}", self.op_str(), GPR_NAMES[self._rs()], GPR_NAMES[self._rt()])
+                            }, Op::Mfhi | Op::Mflo => {
+                                write!(f, "{} {}", self.op_str(), GPR_NAMES[self._rd()])
+                            }, Op::Mthi | Op::Mtlo => {
+                                write!(f, "{} {}", self.op_str(), GPR_NAMES[self._rs()])
+                            }, _ => {
+                                write!(f, "{} {}, {}, {}", self.op_str(), GPR_NAMES[self._rd()], GPR_NAMES[self._rs()], GPR_NAMES[self._rt()])
+                            }
+                        }
                     }, OpC::C => {
                         write!(f, "coprocessor inst... ")
                     }
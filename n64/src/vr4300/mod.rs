@@ -24,17 +24,24 @@ const GPR_NAMES: [&'static str; GPR_SIZE] = [
 
 use std::fmt;
 
+mod cache;
 mod cp0;
 mod cp1;
+mod exception;
 mod instruction;
 mod op;
+mod tlb;
 
+use self::cache::*;
 use self::cp0::*;
 use self::cp1::*;
+use self::exception::*;
 use self::op::*;
 use self::instruction::*;
+use self::tlb::*;
 
 use crate::N64;
+use crate::mc::Bus;
 
 // Implementation of the VR4300 pipeline
 // Does the emulator need to emulate the pipeline?
@@ -51,7 +58,9 @@ use crate::N64;
 
 #[derive(Copy, Clone)]
 pub struct Ic {
-    pub op: Inst
+    pub op: Inst,
+    /* address of the fetched instruction */
+    pub pc: u64
 }
 
 impl fmt::Debug for Ic {
@@ -66,7 +75,10 @@ impl fmt::Debug for Ic {
 pub struct Rf {
     /* RF stage */
     pub rt: u64,
-    pub rs: u64
+    pub rs: u64,
+    /* multiply/divide results */
+    pub hi: u64,
+    pub lo: u64
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -74,8 +86,12 @@ pub struct Ex {
     /* EX stage */
     pub stalled: bool,
     pub ol: u64,
+    /* HI output of a multiply/divide */
+    pub oh: u64,
     pub br: bool,
-    pub wlr: bool
+    pub wlr: bool,
+    /* exception raised by the instruction */
+    pub exc: Option<Exc>
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -91,6 +107,12 @@ pub struct Wb {
 pub struct Pl {
     /* delay slot program counter */
     ds_pc: u64,
+    /* a taken branch is waiting for its delay slot */
+    ds: bool,
+    /* the instruction in the pipeline is in a branch delay slot */
+    bd: bool,
+    /* the pipeline was flushed by an exception */
+    flushed: bool,
 
     pub ic : Ic,
     pub rf: Rf,
@@ -99,9 +121,21 @@ pub struct Pl {
     pub wb: Wb
 }
 
+impl Pl {
+
+    /* Returns the effective address of a LD/ST instruction. */
+    pub fn addr(&self) -> u64 {
+        self.rf.rs.wrapping_add(self.ic.op.offset() as i16 as i64 as u64)
+    }
+}
+
 /* Size of the general purpose register file. */
 const GPR_SIZE: usize = 32;
 
+/* Hardware interrupt lines connected to the VR4300. */
+pub const INTR_RCP: usize = 2;
+pub const INTR_PRENMI: usize = 4;
+
 pub struct VR4300 {
 
     /* 5 stage pipeline */
@@ -112,6 +146,11 @@ pub struct VR4300 {
     /* floating point co-processor */
     pub cp1: CP1,
 
+    /* primary instruction and data caches, bypassed unless enabled */
+    pub icache: Cache,
+    pub dcache: Cache,
+    caches: bool,
+
     pub gpr: [u64; GPR_SIZE],
 
     pub hi: u64,
@@ -128,24 +167,32 @@ impl VR4300 {
 
             pl: Pl {
                 ds_pc: 0,
+                ds: false,
+                bd: false,
+                flushed: false,
 
                 /* IC stage */
                 ic: Ic {
-                    op: Inst(0)
+                    op: Inst(0),
+                    pc: 0
                 },
 
                 /* RF stage */
                 rf: Rf {
                     rt: 0,
-                    rs: 0
+                    rs: 0,
+                    hi: 0,
+                    lo: 0
                 },
 
                 /* EX stage */
                 ex: Ex {
                     stalled: false,
                     ol: 0,
+                    oh: 0,
                     br: false,
-                    wlr: false
+                    wlr: false,
+                    exc: None
                 },
 
                 /* DC stage */
@@ -162,6 +209,10 @@ impl VR4300 {
             cp0: CP0::new(),
             cp1: CP1::new(),
 
+            icache: Cache::new(ICACHE_LINES, ICACHE_LINE_SIZE),
+            dcache: Cache::new(DCACHE_LINES, DCACHE_LINE_SIZE),
+            caches: false,
+
             gpr: [0; GPR_SIZE],
 
             hi: 0,
@@ -186,40 +237,222 @@ impl VR4300 {
         }
     }
 
+    /* Takes a non-maskable interrupt before the next instruction is fetched. */
+    pub fn nmi(&mut self) {
+        /* the next instruction is in a delay slot if a branch is pending */
+        self.pc = self.cp0.nmi(self.pc, self.pl.ds);
+
+        self.ll = 0;
+        self.pl.ds = false;
+    }
+
+    /* Enables or disables the cache models; disabled, every access goes straight to memory. */
+    pub fn set_caches(&mut self, enabled: bool) {
+        self.caches = enabled;
+    }
+
+    /* Drives one of the external interrupt lines. */
+    pub fn set_ip(&mut self, ip: usize, level: bool) {
+        self.cp0.set_ip(ip, level);
+    }
+
+    /* Schedules the target of a taken branch to follow its delay slot,
+       or skips the delay slot of a branch likely that was not taken. */
+    fn branch(&mut self) {
+        if self.pl.ex.br {
+            let offset = ((self.pl.ic.op.offset() as i16 as i32) << 2) as i64;
+            self.pl.ds_pc = (self.pl.ic.pc as i64 + 4 + offset) as u64;
+            self.pl.ds = true;
+        } else if self.pl.ic.op.likely() {
+            self.pc = self.pc.wrapping_add(4);
+        }
+    }
+
+    /* Writes the data of a store to the data cache, or to memory if the caches are bypassed.
+       A store that no device accepts raises a data bus error. */
+    fn store(&mut self, bus: &mut Bus, paddr: u32, cached: bool) -> bool {
+        if self.caches && cached {
+            self.dcache.write(self.daddr(), self.pl.ex.ol, self.pl.ic.op.size());
+        } else if bus.write(paddr, self.pl.ic.op.size(), self.pl.ex.ol).is_err() {
+            self.exception(Exc::Dbe);
+            return false;
+        }
+        true
+    }
+
+    /* Carries out a CACHE operation on the line selected by the effective address.
+       Index operations select the line by the virtual address alone; hit operations translate it. */
+    fn cache(&mut self, bus: &Bus) {
+        if !self.caches {
+            return;
+        }
+
+        let vaddr = self.pl.addr();
+        let op = self.pl.ic.op._rt();
+        let (func, dcache) = (op >> 2, (op & 3) == 1);
+
+        /* there is no secondary cache for the remaining targets to operate on */
+        if (op & 3) >= 2 {
+            return;
+        }
+
+        let paddr = if func >= 3 {
+            match self.cp0.vtop(vaddr, false) {
+                Ok((paddr, _)) => paddr,
+                Err(e) => {
+                    self.exception(e);
+                    return;
+                }
+            }
+        } else {
+            0
+        };
+
+        let cache = if dcache { &mut self.dcache } else { &mut self.icache };
+
+        match (func, dcache) {
+            (0, false) => cache.index_invalidate(vaddr),
+            (0, true) => cache.index_writeback_invalidate(vaddr),
+            (1, _) => self.cp0.wgpr(cache.load_tag(vaddr), CP0_TAGLO),
+            (2, _) => cache.store_tag(vaddr, self.cp0.rgpr(CP0_TAGLO)),
+            (3, true) => cache.create_dirty(vaddr, paddr),
+            (4, _) => cache.hit_invalidate(vaddr, paddr),
+            (5, false) if cache.fill(vaddr, paddr, bus).is_err() => self.exception(Exc::Dbe),
+            (5, true) => cache.hit_writeback_invalidate(vaddr, paddr),
+            (6, _) => cache.hit_writeback(vaddr, paddr, !dcache),
+            _ => {
+
+            }
+        }
+    }
+
+    /* Returns the address of the data accessed by a LD/ST instruction.
+       Unaligned accesses transfer the aligned word or doubleword containing it. */
+    fn daddr(&self) -> u64 {
+        let addr = self.pl.addr();
+        if self.pl.ic.op.merges() {
+            addr & !(self.pl.ic.op.size() as u64 - 1)
+        } else {
+            addr
+        }
+    }
+
+    /* Takes an exception on the instruction in the pipeline and flushes the pipeline. */
+    fn exception(&mut self, e: Exc) {
+        self.pc = self.cp0.exception(e, self.pl.ic.pc, self.pl.bd);
+
+        /* an exception breaks any ll/sc sequence in progress */
+        self.ll = 0;
+
+        /* a pending branch is discarded along with the rest of the pipeline */
+        self.pl.ds = false;
+        self.pl.flushed = true;
+    }
+
     /* IC - Instruction Cache Fetch */
-    pub fn ic<F>(&mut self, rmem: F) where F: Fn(u32) -> u32 {
+    pub fn ic(&mut self, bus: &Bus) {
 
-        /* if there is a branch waiting in the delay slot, we need to update the PC here */
-        if self.pl.ds_pc != 0 {
-            self.pc = self.pl.ds_pc;
-            self.pl.ds_pc = 0;
+        self.pl.flushed = false;
+        self.pl.ic.pc = self.pc;
+
+        /* every instruction issued is one pipeline cycle */
+        self.cp0.tick();
+
+        /* the instruction following a taken branch is in its delay slot */
+        self.pl.bd = self.pl.ds;
+        self.pl.ds = false;
+
+        /* interrupts are taken before the next instruction is fetched */
+        if self.cp0.intr() {
+            self.exception(Exc::Int);
+            return;
+        }
+
+        /* instructions must be fetched from word-aligned addresses */
+        if (self.pc & 3) != 0 {
+            self.exception(Exc::AdEL(self.pc));
+            return;
         }
 
-        let val = rmem((self.pc) as u32);
-        self.pl.ic.op = Inst(val);
+        let (paddr, cached) = match self.cp0.vtop(self.pc, false) {
+            Ok(t) => t,
+            Err(e) => {
+                self.exception(e);
+                return;
+            }
+        };
+
+        let val = if self.caches && cached {
+            if self.icache.hit(self.pc, paddr) {
+                Ok(())
+            } else {
+                self.icache.fill(self.pc, paddr, bus)
+            }.map(|_| self.icache.read(self.pc, 4))
+        } else {
+            bus.read(paddr, 4)
+        };
+
+        /* a fetch that no device answers raises an instruction bus error */
+        match val {
+            Ok(val) => self.pl.ic.op = Inst(val as u32),
+            Err(_) => {
+                self.exception(Exc::Ibe);
+                return;
+            }
+        }
 
         println!("{:#?}\n", self.pl.ic);
 
-        self.pc += 4;
+        /* if there is a branch waiting in the delay slot, continue at its target afterwards */
+        if self.pl.bd {
+            self.pc = self.pl.ds_pc;
+        } else {
+            self.pc += 4;
+        }
     }
 
     /* RF - Register Fetch */
     pub fn rf(&mut self) {
-        match self.pl.ic.op.class() {
-            OpC::C => {
-                self.pl.rf.rs = self.cp0.rgpr(self.pl.ic.op._rd()) as u64
+        if self.pl.flushed {
+            return;
+        }
+
+        /* the FPU register layout follows Status.FR */
+        self.cp1.fr = self.cp0.fr();
+
+        match (self.pl.ic.op.class(), self.pl.ic.op.kind()) {
+            (OpC::C, Op::Cop1) => {
+                self.pl.rf.rs = self.cp1.rmov(self.pl.ic.op.op(), self.pl.ic.op._rd())
+            }, (OpC::C, _) => {
+                self.pl.rf.rs = self.cp0.rgpr(self.pl.ic.op._rd())
             }, _ => {
                 self.pl.rf.rs = self.rgpr(self.pl.ic.op._rs());
             }
         }
 
-        self.pl.rf.rt = self.rgpr(self.pl.ic.op._rt());
+        /* FPU stores take their data from the floating-point register ft */
+        self.pl.rf.rt = match self.pl.ic.op.op() {
+            Op::Swc1 => self.cp1.r32(self.pl.ic.op._rt()) as u64,
+            Op::Sdc1 => self.cp1.r64(self.pl.ic.op._rt()),
+            _ => self.rgpr(self.pl.ic.op._rt())
+        };
+
+        self.pl.rf.hi = self.hi;
+        self.pl.rf.lo = self.lo;
 
         println!("{:#?}\n", self.pl.rf);
     }
 
     /* EX - Execution */
     pub fn ex(&mut self) {
+        if self.pl.flushed {
+            return;
+        }
+
+        self.pl.ex.br = false;
+        self.pl.ex.wlr = false;
+        self.pl.ex.exc = None;
+
         // /* stall if the register is the dest of the RF instruction */
         // match self.pl.ic.op.class() {
         //
@@ -240,84 +473,256 @@ impl VR4300 {
 
         println!("CLASS: {:?}", self.pl.ic.op.class());
 
-        match self.pl.ic.op.op() {
-            Op::Syscall => {
-                if self.pl.ic.op.sa() > 0 {
-                    let result = if self.pl.ic.op._rt() == 16 { "Pass" }  else { "Fail" };
-                    println!("t #Result - ISA:{:X}  Set:{:X}  Test:{:X}  Result:{:?}", self.pl.ic.op._rs(), self.pl.ic.op._rd(), self.pl.ic.op.sa(), result);
-                }
-            }, _ => {
-                match self.pl.ic.op.class() {
-                    OpC::L => {
-
-                    }, OpC::C => {
+        /* coprocessor instructions trap unless Status.CU marks the coprocessor usable */
+        if let Some(cop) = self.pl.ic.op.cop() {
+            if !self.cp0.usable(cop) {
+                self.exception(Exc::CpU(cop));
+                return;
+            }
 
-                    }, OpC::B => {
-                        self.pl.ic.op.ex()(&mut self.pl);
+            /* there is no coprocessor 2 to execute them even when it is marked usable */
+            if cop == 2 {
+                self.exception(Exc::RI);
+                return;
+            }
+        }
 
-                        /* if a branch will occur, set the delay slot program counter */
-                        if self.pl.ex.br {
-                            let offset = ((self.pl.ic.op.offset() as i16 as i32) << 2) as i64;
-                            self.pl.ds_pc = (self.pc as i64 + offset) as u64;
+        match self.pl.ic.op.class() {
+            OpC::L => {
+                /* loads execute once their memory has been read */
+            }, OpC::S if self.pl.ic.op.merges() => {
+                /* as do stores that merge into the memory they write */
+            }, OpC::C => {
+                match self.pl.ic.op.kind() {
+                    Op::Cop0 => {
+                        match self.pl.ic.op.op() {
+                            Op::Tlbr | Op::Tlbwi | Op::Tlbwr | Op::Tlbp => {
+                                self.cp0.exec(self.pl.ic.op);
+                            }, Op::Eret => {
+                                /* eret has no delay slot; execution resumes at the exception pc */
+                                self.pc = self.cp0.eret();
+                                self.ll = 0;
+                            }, Op::Cf | Op::Ct | Op::Bcf | Op::Bct | Op::Bcfl | Op::Bctl => {
+                                /* cp0 has no control registers or condition signal */
+                                self.exception(Exc::RI);
+                                return;
+                            }, _ => {
+                                self.pl.ic.op.ex()(&mut self.pl);
+                            }
+                        }
+                    }, Op::Cop1 => {
+                        match self.pl.ic.op.op() {
+                            Op::Mf | Op::Dmf | Op::Cf | Op::Mt | Op::Dmt | Op::Ct => {
+                                self.pl.ic.op.ex()(&mut self.pl);
+                            }, Op::Bcf | Op::Bct | Op::Bcfl | Op::Bctl => {
+                                let t = matches!(self.pl.ic.op.op(), Op::Bct | Op::Bctl);
+                                self.pl.ex.br = self.cp1.c() == t;
+                                self.branch();
+                            }, _ => {
+                                if let Err(e) = self.cp1.exec(self.pl.ic.op) {
+                                    self.pl.ex.exc = Some(e);
+                                }
+                            }
                         }
-
-                    }, OpC::J => {
-
-
                     }, _ => {
-                        self.pl.ic.op.ex()(&mut self.pl);
+
                     }
                 }
+            }, OpC::B => {
+                self.pl.ic.op.ex()(&mut self.pl);
+
+                /* if a branch will occur, set the delay slot program counter */
+                self.branch();
+
+            }, OpC::J => {
+                self.pl.ic.op.ex()(&mut self.pl);
+
+                /* jumps are always taken, to the target left in ol */
+                self.pl.ds_pc = self.pl.ex.ol;
+                self.pl.ds = true;
+            }, _ => {
+                self.pl.ic.op.ex()(&mut self.pl);
             }
         }
 
         println!("{:#?}\n", self.pl.ex);
+
+        if let Some(e) = self.pl.ex.exc {
+            self.exception(e);
+        }
     }
 
     /* DC - Data Cache Fetch */
-    pub fn dc<F>(&mut self, rmem: F) where F: Fn(u32) -> u32 {
+    pub fn dc(&mut self, bus: &Bus) {
+        if self.pl.flushed {
+            return;
+        }
 
-        match self.pl.ic.op.class() {
-            OpC::L => {
-                let base = self.pl.rf.rs as i64;
-                let offset = self.pl.ic.op.offset() as i16 as i64;
-                self.pl.dc.dc = rmem((base + offset) as u32) as u64;
-                /* need to call the ex function as a hack to get ol populated */
-                self.pl.ic.op.ex()(&mut self.pl);
-            }, _ => {
+        /* unaligned stores read the memory they merge into */
+        let (load, store) = match self.pl.ic.op.class() {
+            OpC::L => (true, false),
+            OpC::S => (self.pl.ic.op.merges(), true),
+            _ => (false, false)
+        };
+
+        /* apart from the unaligned family, accesses must be aligned to their size */
+        let addr = self.pl.addr();
+        if (load || store) && !self.pl.ic.op.merges() && (addr & (self.pl.ic.op.size() as u64 - 1)) != 0 {
+            self.exception(if store { Exc::AdES(addr) } else { Exc::AdEL(addr) });
+            return;
+        }
+
+        if let Op::Cache = self.pl.ic.op.op() {
+            self.cache(bus);
+            return;
+        }
+
+        if !load && !store {
+            return;
+        }
 
+        let vaddr = self.daddr();
+        let (paddr, cached) = match self.cp0.vtop(vaddr, store) {
+            Ok(t) => t,
+            Err(e) => {
+                self.exception(e);
+                return;
             }
+        };
+
+        /* the watchpoint traps the access before it reaches the cache or memory */
+        if self.cp0.watch(paddr, store) {
+            self.exception(Exc::Watch);
+            return;
+        }
+
+        /* cached loads and stores both bring the line into the data cache */
+        let cached = self.caches && cached;
+        if cached && !self.dcache.hit(vaddr, paddr) && self.dcache.fill(vaddr, paddr, bus).is_err() {
+            self.exception(Exc::Dbe);
+            return;
+        }
+
+        if load {
+            self.pl.dc.dc = if cached {
+                self.dcache.read(vaddr, self.pl.ic.op.size())
+            } else {
+                match bus.read(paddr, self.pl.ic.op.size()) {
+                    Ok(val) => val,
+                    Err(_) => {
+                        self.exception(Exc::Dbe);
+                        return;
+                    }
+                }
+            };
+
+            /* ll and lld start a read-modify-write sequence on the physical address */
+            match self.pl.ic.op.op() {
+                Op::Ll | Op::Lld => {
+                    self.ll = 1;
+                    self.cp0.wgpr((paddr >> 4) as u64, CP0_LLADDR);
+                }, _ => {
+
+                }
+            }
+
+            /* need to call the ex function as a hack to get ol populated */
+            self.pl.ic.op.ex()(&mut self.pl);
         }
 
         println!("{:#?}\n", self.pl.dc);
     }
 
     /* WB - Write Back */
-    pub fn wb<F>(&mut self, mut wmem: F) where F: FnMut(u32, u32) {
+    pub fn wb(&mut self, bus: &mut Bus) {
+        /* lines written back by the caches reach memory even when the pipeline was flushed */
+        self.icache.drain(bus);
+        self.dcache.drain(bus);
+
+        if self.pl.flushed {
+            return;
+        }
 
         match self.pl.ic.op.class() {
 
             /* decode instruction types I, L, S, J, B, R, C for writeback */
 
-            OpC::I | OpC::L => {
-                /* I and L instructions write back to the rt register */
-                self.wgpr(self.pl.ex.ol, self.pl.ic.op._rt());
+            OpC::I | OpC::R if self.pl.ic.op.trap() => {
+                /* traps that were not taken have nothing to write back */
+            }, OpC::I | OpC::L => {
+                /* I and L instructions write back to the rt register, FPU loads to ft */
+                match self.pl.ic.op.op() {
+                    Op::Lwc1 => self.cp1.w32(self.pl.ex.ol as u32, self.pl.ic.op._rt()),
+                    Op::Ldc1 => self.cp1.w64(self.pl.ex.ol, self.pl.ic.op._rt()),
+                    Op::Cache => {
+                        /* cache operations were carried out in DC and have nothing to write back */
+                    },
+                    _ => self.wgpr(self.pl.ex.ol, self.pl.ic.op._rt())
+                }
             }, OpC::S => {
                 /* S instructions write back to memory */
-                let base = self.pl.rf.rs as i64;
-                let offset = self.pl.ic.op.offset() as i16 as i64;
-                wmem((base + offset) as u64 as u32, self.pl.ex.ol as u32);
+                match self.cp0.vtop(self.daddr(), true) {
+                    Ok((paddr, cached)) => match self.pl.ic.op.op() {
+                        Op::Sc | Op::Scd => {
+                            /* sc and scd only store if nothing broke the sequence since ll, and report whether they did */
+                            if self.ll == 0 || self.store(bus, paddr, cached) {
+                                self.wgpr(self.ll as u64, self.pl.ic.op._rt());
+                            }
+                        }, _ => {
+                            self.store(bus, paddr, cached);
+                        }
+                    },
+                    Err(e) => self.exception(e)
+                }
             }, OpC::J | OpC::B => {
                 /* J and B instructions wrote to the delay slot program counter and link register */
                 if self.pl.ex.wlr {
-                    self.wgpr(self.pc + 4, 31);
+                    /* the return address is the instruction following the delay slot */
+                    let reg = match self.pl.ic.op.op() {
+                        Op::Jalr => self.pl.ic.op._rd(),
+                        _ => 31
+                    };
+                    self.wgpr(self.pl.ic.pc.wrapping_add(8), reg);
                 }
             }, OpC::R => {
-                /* write back to rd */
-                self.wgpr(self.pl.ex.ol, self.pl.ic.op._rd());
+                match self.pl.ic.op.op() {
+                    Op::Mult | Op::Multu | Op::Div | Op::Divu | Op::Dmult | Op::Dmultu | Op::Ddiv | Op::Ddivu => {
+                        /* multiplies and divides write back to HI and LO */
+                        self.hi = self.pl.ex.oh;
+                        self.lo = self.pl.ex.ol;
+                    }, Op::Mthi => {
+                        self.hi = self.pl.ex.ol;
+                    }, Op::Mtlo => {
+                        self.lo = self.pl.ex.ol;
+                    }, _ => {
+                        /* write back to rd */
+                        self.wgpr(self.pl.ex.ol, self.pl.ic.op._rd());
+                    }
+                }
             }, OpC::C => {
-                /* write back to rt on the coprocessor */
-                self.cp0.wgpr(self.pl.ex.ol as u32, self.pl.ic.op._rt());
+                match (self.pl.ic.op.kind(), self.pl.ic.op.op()) {
+                    (Op::Cop0, Op::Tlbr) | (Op::Cop0, Op::Tlbwi) | (Op::Cop0, Op::Tlbwr) | (Op::Cop0, Op::Tlbp) | (Op::Cop0, Op::Eret) => {
+                        /* TLB instructions and eret operate on the cop0 registers directly */
+                    }, (Op::Cop0, Op::Mf) | (Op::Cop0, Op::Dmf) => {
+                        /* moves from cop0 write back to rt on the cpu */
+                        self.wgpr(self.pl.ex.ol, self.pl.ic.op._rt());
+                    }, (Op::Cop0, Op::Mt) | (Op::Cop0, Op::Dmt) => {
+                        /* moves to cop0 write back to rd on the coprocessor */
+                        self.cp0.wgpr(self.pl.ex.ol, self.pl.ic.op._rd());
+                    }, (Op::Cop0, _) => {
+                        /* other cop0 instructions have no result to write back */
+                    }, (Op::Cop1, Op::Mf) | (Op::Cop1, Op::Dmf) | (Op::Cop1, Op::Cf) => {
+                        /* moves from cop1 write back to rt on the cpu */
+                        self.wgpr(self.pl.ex.ol, self.pl.ic.op._rt());
+                    }, (Op::Cop1, Op::Mt) | (Op::Cop1, Op::Dmt) | (Op::Cop1, Op::Ct) => {
+                        /* moves to cop1 write back to fs */
+                        if let Err(e) = self.cp1.wmov(self.pl.ic.op.op(), self.pl.ex.ol, self.pl.ic.op._rd()) {
+                            self.exception(e);
+                        }
+                    }, _ => {
+                        /* other cop1 instructions write back to their own registers */
+                    }
+                }
             }
         }
 
@@ -357,3 +762,443 @@ impl fmt::Debug for VR4300 {
 
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::N64;
+    use crate::Ram;
+    use crate::Read;
+    use crate::Write;
+
+    fn i(op: u32, rs: u32, rt: u32, imm: u16) -> u32 {
+        (op << 26) | (rs << 21) | (rt << 16) | imm as u32
+    }
+
+    fn lui(rt: u32, imm: u16) -> u32 { i(0x0F, 0, rt, imm) }
+    fn ori(rt: u32, rs: u32, imm: u16) -> u32 { i(0x0D, rs, rt, imm) }
+    fn addiu(rt: u32, rs: u32, imm: u16) -> u32 { i(0x09, rs, rt, imm) }
+    fn lw(rt: u32, base: u32) -> u32 { i(0x23, base, rt, 0) }
+    fn ld(rt: u32, base: u32) -> u32 { i(0x37, base, rt, 0) }
+    fn ll(rt: u32, base: u32) -> u32 { i(0x30, base, rt, 0) }
+    fn lld(rt: u32, base: u32) -> u32 { i(0x34, base, rt, 0) }
+    fn sc(rt: u32, base: u32) -> u32 { i(0x38, base, rt, 0) }
+    fn scd(rt: u32, base: u32) -> u32 { i(0x3C, base, rt, 0) }
+    fn sw(rt: u32, base: u32) -> u32 { i(0x2B, base, rt, 0) }
+    fn jr(rs: u32) -> u32 { (rs << 21) | 0x08 }
+    fn cache(op: u32, base: u32) -> u32 { i(0x2F, base, op, 0) }
+    fn mtc0(rt: u32, rd: u32) -> u32 { (0x10 << 26) | (0x04 << 21) | (rt << 16) | (rd << 11) }
+    const ERET: u32 = 0x4200_0018;
+    const SYSCALL: u32 = 0x0000_000C;
+    const NOP: u32 = 0;
+
+    /* Offset of the general exception vector in the PIF ROM while Status.BEV is set. */
+    const BEV_GENERAL: usize = 0x380;
+
+    /* Boots a console whose PIF ROM holds the program, with a handler at the BEV general exception vector. */
+    fn boot(prog: &[u32], handler: &[u32]) -> N64 {
+        let mut prom = vec![0u8; 0x7C0];
+        for (k, w) in prog.iter().enumerate() {
+            prom[k * 4..k * 4 + 4].copy_from_slice(&w.to_be_bytes());
+        }
+        for (k, w) in handler.iter().enumerate() {
+            prom[BEV_GENERAL + k * 4..BEV_GENERAL + k * 4 + 4].copy_from_slice(&w.to_be_bytes());
+        }
+        N64::new(vec![0u8; 0x1000].into_boxed_slice(), prom.into_boxed_slice(), Ram::Base)
+    }
+
+    fn run(n: &mut N64, steps: usize) {
+        for _ in 0..steps {
+            n.step();
+        }
+    }
+
+    /* Returns Cause.ExcCode. */
+    fn exccode(n: &N64) -> u64 {
+        (n.cpu.cp0.rgpr(13) >> 2) & 0x1F
+    }
+
+    /* t0 points at the RSP's DMEM, which the tests use as memory. */
+    const T0: u32 = 8;
+    const T1: u32 = 9;
+    const T2: u32 = 10;
+    const T3: u32 = 11;
+    const T4: u32 = 12;
+
+    #[test]
+    fn sc_succeeds_after_ll() {
+        let mut n = boot(&[lui(T0, 0xA400), ll(T2, T0), addiu(T1, 0, 5), sc(T1, T0), lw(T4, T0)], &[]);
+        run(&mut n, 5);
+
+        assert_eq!(n.cpu.gpr[T1 as usize], 1);
+        assert_eq!(n.cpu.gpr[T4 as usize], 5);
+    }
+
+    #[test]
+    fn sc_fails_without_ll() {
+        let mut n = boot(&[lui(T0, 0xA400), addiu(T1, 0, 5), sc(T1, T0), lw(T4, T0)], &[]);
+        run(&mut n, 4);
+
+        assert_eq!(n.cpu.gpr[T1 as usize], 0);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0);
+    }
+
+    #[test]
+    fn eret_clears_ll() {
+        /* EPC points past the eret, at the sc sequence */
+        let mut n = boot(&[
+            lui(T0, 0xA400),
+            lui(T3, 0xBFC0),
+            ori(T3, T3, 0x18),
+            mtc0(T3, 14),
+            ll(T2, T0),
+            ERET,
+            addiu(T1, 0, 5),
+            sc(T1, T0),
+            lw(T4, T0)
+        ], &[]);
+        run(&mut n, 9);
+
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_0024);
+        assert_eq!(n.cpu.ll, 0);
+        assert_eq!(n.cpu.gpr[T1 as usize], 0);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0);
+    }
+
+    #[test]
+    fn exception_clears_ll() {
+        /* Status.BEV keeps the exception vector in the PIF ROM */
+        let mut n = boot(&[
+            lui(T0, 0xA400),
+            lui(T3, 0x0040),
+            mtc0(T3, 12),
+            lld(T2, T0),
+            SYSCALL
+        ], &[
+            addiu(T1, 0, 5),
+            scd(T1, T0),
+            ld(T4, T0)
+        ]);
+        run(&mut n, 4);
+        assert_eq!(n.cpu.ll, 1);
+
+        run(&mut n, 4);
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_038C);
+        assert_eq!(n.cpu.ll, 0);
+        assert_eq!(n.cpu.gpr[T1 as usize], 0);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0);
+    }
+
+    #[test]
+    fn load_bus_error() {
+        /* nothing answers at physical 0x04900000 */
+        let mut n = boot(&[lui(T3, 0x0040), mtc0(T3, 12), lui(T0, 0xA490), lw(T4, T0)], &[]);
+        run(&mut n, 4);
+
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_0380);
+        assert_eq!(exccode(&n), 0x07);
+        assert_eq!(n.cpu.cp0.rgpr(14), 0xFFFF_FFFF_9FC0_000C);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0);
+    }
+
+    #[test]
+    fn store_bus_error() {
+        /* the cartridge ROM refuses writes */
+        let mut n = boot(&[lui(T3, 0x0040), mtc0(T3, 12), lui(T0, 0xB000), sw(T3, T0)], &[]);
+        run(&mut n, 4);
+
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_0380);
+        assert_eq!(exccode(&n), 0x07);
+        assert_eq!(n.cpu.cp0.rgpr(14), 0xFFFF_FFFF_9FC0_000C);
+    }
+
+    #[test]
+    fn fetch_bus_error() {
+        let mut n = boot(&[lui(T3, 0x0040), mtc0(T3, 12), lui(T0, 0xA490), jr(T0), NOP], &[]);
+        run(&mut n, 6);
+
+        assert_eq!(n.cpu.pc, 0xFFFF_FFFF_BFC0_0380);
+        assert_eq!(exccode(&n), 0x06);
+        assert_eq!(n.cpu.cp0.rgpr(14), 0xFFFF_FFFF_A490_0000);
+    }
+
+    #[test]
+    fn uncached_bypass() {
+        /* t0 and t1 address the start of RDRAM through kseg0 and kseg1 */
+        let mut n = boot(&[
+            lui(T0, 0x8000),
+            lui(T1, 0xA000),
+            addiu(T2, 0, 5),
+            sw(T2, T0),
+            lw(T3, T1),
+            lw(T4, T0),
+            cache(0x15, T0),
+            lw(T3, T1)
+        ], &[]);
+        n.set_caches(true);
+
+        /* the store stays in the data cache, where kseg1 loads do not look */
+        run(&mut n, 6);
+        assert_eq!(n.cpu.gpr[T3 as usize], 0);
+        assert_eq!(n.cpu.gpr[T4 as usize], 5);
+
+        /* Hit Writeback Invalidate pushes it out to memory */
+        run(&mut n, 2);
+        assert_eq!(n.cpu.gpr[T3 as usize], 5);
+        assert!(!n.cpu.dcache.hit(0xFFFF_FFFF_8000_0000, 0));
+    }
+
+    #[test]
+    fn count_compare() {
+        let mut n = boot(&[addiu(T0, 0, 3), mtc0(T0, 11), NOP, NOP, NOP, NOP, mtc0(T0, 11)], &[]);
+
+        /* Count advances every other instruction */
+        run(&mut n, 1);
+        assert_eq!(n.cpu.cp0.rgpr(9), 0);
+        run(&mut n, 1);
+        assert_eq!(n.cpu.cp0.rgpr(9), 1);
+        run(&mut n, 3);
+        assert_eq!(n.cpu.cp0.rgpr(9), 2);
+        assert_eq!(n.cpu.cp0.rgpr(13) & (1 << 15), 0);
+
+        /* reaching Compare raises IP7 */
+        run(&mut n, 1);
+        assert_eq!(n.cpu.cp0.rgpr(9), 3);
+        assert_eq!(n.cpu.cp0.rgpr(13) & (1 << 15), 1 << 15);
+
+        /* writing Compare clears it */
+        run(&mut n, 1);
+        assert_eq!(n.cpu.cp0.rgpr(13) & (1 << 15), 0);
+    }
+
+    /* The doubleword at the start of DMEM and the register merged with it by the unaligned loads and stores. */
+    const MEM: [u8; 8] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+    const REG: u64 = 0xF0E1_D2C3_B4A5_9687;
+
+    /* Runs a single unaligned load or store of t1 with t0 pointing at MEM. */
+    fn unaligned(op: u32, offset: usize) -> N64 {
+        let mut n = boot(&[i(op, T0, T1, offset as u16)], &[]);
+        n.write64(0xA400_0000, u64::from_be_bytes(MEM)).unwrap();
+        n.cpu.gpr[T0 as usize] = 0xFFFF_FFFF_A400_0000;
+        n.cpu.gpr[T1 as usize] = REG;
+        run(&mut n, 1);
+        n
+    }
+
+    fn mem(n: &N64) -> [u8; 8] {
+        n.read64(0xA400_0000).unwrap().to_be_bytes()
+    }
+
+    #[test]
+    fn lwl_lwr() {
+        assert_eq!(unaligned(0x22, 1).cpu.gpr[T1 as usize], 0x1122_3387);
+        assert_eq!(unaligned(0x26, 1).cpu.gpr[T1 as usize], 0xF0E1_D2C3_B4A5_0011);
+
+        for a in 0..8 {
+            let (k, w) = (a & 3, &MEM[a & 4..(a & 4) + 4]);
+
+            /* lwl loads the bytes from the address to the end of the word into the top of the register */
+            let mut r = (REG as u32).to_be_bytes();
+            r[..4 - k].copy_from_slice(&w[k..]);
+            let n = unaligned(0x22, a);
+            assert_eq!(n.cpu.gpr[T1 as usize], u32::from_be_bytes(r) as i32 as i64 as u64, "lwl {}", a);
+
+            /* lwr loads the bytes from the start of the word to the address into the bottom */
+            let mut r = (REG as u32).to_be_bytes();
+            r[3 - k..].copy_from_slice(&w[..=k]);
+            let r = u32::from_be_bytes(r);
+            let n = unaligned(0x26, a);
+            let expected = if k == 3 { r as i32 as i64 as u64 } else { (REG & 0xFFFF_FFFF_0000_0000) | r as u64 };
+            assert_eq!(n.cpu.gpr[T1 as usize], expected, "lwr {}", a);
+        }
+    }
+
+    #[test]
+    fn ldl_ldr() {
+        for k in 0..8 {
+            let mut r = REG.to_be_bytes();
+            r[..8 - k].copy_from_slice(&MEM[k..]);
+            let n = unaligned(0x1A, k);
+            assert_eq!(n.cpu.gpr[T1 as usize], u64::from_be_bytes(r), "ldl {}", k);
+
+            let mut r = REG.to_be_bytes();
+            r[7 - k..].copy_from_slice(&MEM[..=k]);
+            let n = unaligned(0x1B, k);
+            assert_eq!(n.cpu.gpr[T1 as usize], u64::from_be_bytes(r), "ldr {}", k);
+        }
+    }
+
+    #[test]
+    fn swl_swr() {
+        let r = (REG as u32).to_be_bytes();
+
+        for a in 0..8 {
+            let (k, base) = (a & 3, a & 4);
+
+            /* swl stores the top of the register from the address to the end of the word */
+            let mut m = MEM;
+            m[a..base + 4].copy_from_slice(&r[..4 - k]);
+            assert_eq!(mem(&unaligned(0x2A, a)), m, "swl {}", a);
+
+            /* swr stores the bottom of the register from the start of the word to the address */
+            let mut m = MEM;
+            m[base..=a].copy_from_slice(&r[3 - k..]);
+            assert_eq!(mem(&unaligned(0x2E, a)), m, "swr {}", a);
+        }
+    }
+
+    #[test]
+    fn sdl_sdr() {
+        let r = REG.to_be_bytes();
+
+        for k in 0..8 {
+            let mut m = MEM;
+            m[k..].copy_from_slice(&r[..8 - k]);
+            assert_eq!(mem(&unaligned(0x2C, k)), m, "sdl {}", k);
+
+            let mut m = MEM;
+            m[..=k].copy_from_slice(&r[7 - k..]);
+            assert_eq!(mem(&unaligned(0x2D, k)), m, "sdr {}", k);
+        }
+    }
+
+    fn r(rs: u32, rt: u32, rd: u32, funct: u32) -> u32 { (rs << 21) | (rt << 16) | (rd << 11) | funct }
+    fn regimm(rs: u32, rt: u32, imm: u16) -> u32 { i(0x01, rs, rt, imm) }
+
+    /* The general exception vector while Status.BEV is clear. */
+    const GENERAL: u64 = 0xFFFF_FFFF_8000_0180;
+
+    /* Runs a single instruction with t0 and t1 as its operands and t2 holding a marker. */
+    fn exec(inst: u32, rs: u64, rt: u64) -> N64 {
+        let mut n = boot(&[inst], &[]);
+        n.cpu.gpr[T0 as usize] = rs;
+        n.cpu.gpr[T1 as usize] = rt;
+        n.cpu.gpr[T2 as usize] = 0x5A5A;
+        run(&mut n, 1);
+        n
+    }
+
+    #[test]
+    fn overflow() {
+        let add = r(T0, T1, T2, 0x20);
+        let addi = i(0x08, T0, T2, 1);
+        let dadd = r(T0, T1, T2, 0x2C);
+
+        for &(inst, rs, rt) in &[(add, 0x7FFF_FFFF, 1), (addi, 0x7FFF_FFFF, 0), (dadd, 0x7FFF_FFFF_FFFF_FFFF, 1)] {
+            /* the exception is taken at the instruction and rd is left alone */
+            let n = exec(inst, rs, rt);
+            assert_eq!(n.cpu.pc, GENERAL, "{:#x}", inst);
+            assert_eq!(exccode(&n), 0x0C);
+            assert_eq!(n.cpu.cp0.rgpr(14), 0xFFFF_FFFF_9FC0_0000);
+            assert_eq!(n.cpu.gpr[T2 as usize], 0x5A5A);
+        }
+
+        /* without overflow the sum is written */
+        assert_eq!(exec(add, 0x7FFF_FFFE, 1).cpu.gpr[T2 as usize], 0x7FFF_FFFF);
+        assert_eq!(exec(addi, 0xFFFF_FFFF_FFFF_FFFF, 0).cpu.gpr[T2 as usize], 0);
+        assert_eq!(exec(dadd, 0x7FFF_FFFF_FFFF_FFFE, 1).cpu.gpr[T2 as usize], 0x7FFF_FFFF_FFFF_FFFF);
+    }
+
+    #[test]
+    fn traps() {
+        let minus_one = 0xFFFF_FFFF_FFFF_FFFF;
+
+        /* (instruction, rs, rt, taken) */
+        let cases = [
+            (r(T0, T1, 0, 0x30), minus_one, 0, false),
+            (r(T0, T1, 0, 0x30), 0, minus_one, true),
+            (r(T0, T1, 0, 0x31), minus_one, 0, true),
+            (r(T0, T1, 0, 0x32), minus_one, 0, true),
+            (r(T0, T1, 0, 0x33), minus_one, 0, false),
+            (r(T0, T1, 0, 0x34), 7, 7, true),
+            (r(T0, T1, 0, 0x34), 7, 8, false),
+            (r(T0, T1, 0, 0x36), 7, 8, true),
+            (r(T0, T1, 0, 0x36), 7, 7, false),
+            (regimm(T0, 0x08, 0xFFFF), 0, 0, true),
+            (regimm(T0, 0x09, 0xFFFF), 0, 0, false),
+            (regimm(T0, 0x0A, 0xFFFF), 0, 0, false),
+            (regimm(T0, 0x0B, 0xFFFF), 0, 0, true),
+            (regimm(T0, 0x0C, 5), 5, 0, true),
+            (regimm(T0, 0x0C, 5), 6, 0, false),
+            (regimm(T0, 0x0E, 5), 6, 0, true)
+        ];
+
+        for &(inst, rs, rt, taken) in cases.iter() {
+            let n = exec(inst, rs, rt);
+            if taken {
+                assert_eq!(n.cpu.pc, GENERAL, "{:#x}", inst);
+                assert_eq!(exccode(&n), 0x0D);
+                assert_eq!(n.cpu.cp0.rgpr(14), 0xFFFF_FFFF_9FC0_0000);
+            } else {
+                assert_eq!(n.cpu.pc, 0xFFFF_FFFF_9FC0_0004, "{:#x}", inst);
+            }
+        }
+    }
+
+    #[test]
+    fn unsigned_divide() {
+        let divu = r(T0, T1, 0, 0x1B);
+        let ddivu = r(T0, T1, 0, 0x1F);
+
+        let n = exec(divu, 7, 2);
+        assert_eq!((n.cpu.lo, n.cpu.hi), (3, 1));
+        let n = exec(ddivu, 0xFFFF_FFFF_FFFF_FFFF, 0x10);
+        assert_eq!((n.cpu.lo, n.cpu.hi), (0x0FFF_FFFF_FFFF_FFFF, 0xF));
+
+        /* dividing by zero leaves all ones in LO and the dividend in HI */
+        let n = exec(divu, 0x8000_0000, 0);
+        assert_eq!((n.cpu.lo, n.cpu.hi), (0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_8000_0000));
+        let n = exec(ddivu, 0x1234, 0);
+        assert_eq!((n.cpu.lo, n.cpu.hi), (0xFFFF_FFFF_FFFF_FFFF, 0x1234));
+    }
+
+    #[test]
+    fn branch_likely_nullifies_delay_slot() {
+        /* (opcode, t0, taken): beq and beql over a delay slot setting t2, then t3, to a target setting t4 */
+        for &(op, t0, taken) in &[(0x14, 1, false), (0x14, 0, true), (0x04, 1, false), (0x04, 0, true)] {
+            let mut n = boot(&[i(op, T0, T1, 3), addiu(T2, 0, 1), addiu(T3, 0, 1), NOP, addiu(T4, 0, 1)], &[]);
+            n.cpu.gpr[T0 as usize] = t0;
+            run(&mut n, 3);
+
+            let likely = op == 0x14;
+            let regs = (n.cpu.gpr[T2 as usize], n.cpu.gpr[T3 as usize], n.cpu.gpr[T4 as usize]);
+            match (likely, taken) {
+                /* a branch likely that is not taken skips its delay slot */
+                (true, false) => assert_eq!(regs, (0, 1, 0)),
+                (false, false) => assert_eq!(regs, (1, 1, 0)),
+                (_, true) => assert_eq!(regs, (1, 0, 1))
+            }
+        }
+    }
+
+    /* Runs a load or store of t1 at the start of DMEM with WatchLo set from t3 and t4 holding a marker. */
+    fn watch(inst: u32, watchlo: u64) -> N64 {
+        let mut n = boot(&[mtc0(T3, 18), inst], &[]);
+        n.write(0xA400_0000, 0x1234).unwrap();
+        n.cpu.gpr[T0 as usize] = 0xFFFF_FFFF_A400_0000;
+        n.cpu.gpr[T1 as usize] = 5;
+        n.cpu.gpr[T3 as usize] = watchlo;
+        n.cpu.gpr[T4 as usize] = 0x5A5A;
+        run(&mut n, 2);
+        n
+    }
+
+    #[test]
+    fn watch_exceptions() {
+        /* WatchLo holds the doubleword's physical address with the R (1) and W (0) enables */
+        let n = watch(lw(T4, T0), 0x0400_0000 | 2);
+        assert_eq!(n.cpu.pc, GENERAL);
+        assert_eq!(exccode(&n), 0x17);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0x5A5A);
+
+        let n = watch(sw(T1, T0), 0x0400_0000 | 1);
+        assert_eq!(n.cpu.pc, GENERAL);
+        assert_eq!(exccode(&n), 0x17);
+        assert_eq!(n.read(0xA400_0000), Ok(0x1234));
+
+        /* accesses the watchpoint is not enabled for, or to other doublewords, go ahead */
+        let n = watch(sw(T1, T0), 0x0400_0000 | 2);
+        assert_eq!(n.read(0xA400_0000), Ok(5));
+        let n = watch(lw(T4, T0), 0x0400_0008 | 3);
+        assert_eq!(n.cpu.gpr[T4 as usize], 0x1234);
+    }
+}
@@ -76,6 +76,17 @@ pub enum Op {
     /**/        /**/        /**/        /**/        /**/        /**/        /**/        /**/
     /**/        /**/        /**/        /**/        /**/        /**/        /**/        /**/
 
+    /* CP1 opcodes */
+
+    Fadd,       Fsub,       Fmul,       Fdiv,       Fsqrt,      Fabs,       Fmov,       Fneg,
+    FroundL,    FtruncL,    FceilL,     FfloorL,    FroundW,    FtruncW,    FceilW,     FfloorW,
+    /**/        /**/        /**/        /**/        /**/        /**/        /**/        /**/
+    /**/        /**/        /**/        /**/        /**/        /**/        /**/        /**/
+    Fcvts,      Fcvtd,      /**/        /**/        Fcvtw,      Fcvtl,      /**/        /**/
+    /**/        /**/        /**/        /**/        /**/        /**/        /**/        /**/
+    Fcf,        Fcun,       Fceq,       Fcueq,      Fcolt,      Fcult,      Fcole,      Fcule,
+    Fcsf,       Fcngle,     Fcseq,      Fcngl,      Fclt,       Fcnge,      Fcle,       Fcngt,
+
     Reserved
 }
 
@@ -85,10 +96,14 @@ pub type OpF = &'static Fn(&mut Pl);
 
 pub type OpTup = (Op, &'static str, OpC, OpF);
 
+/* Reserved encodings raise a reserved instruction exception. */
 const RESERVED: OpTup = (Op::Reserved, "reserved", OpC::R, &|p| {
-    unimplemented!()
+    p.ex.exc = Some(Exc::RI)
 });
 
+/* Undefined CP1 operations are executed by CP1::exec as unimplemented operations. */
+const CP1_RESERVED: OpTup = (Op::Reserved, "reserved", OpC::C, &|_| {});
+
 /* A constant 2-d array of the opcode p.dc.dcues. */
 
 pub const OP_TABLE: [[&OpTup; 8]; 8] = [
@@ -103,12 +118,15 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
         unimplemented!()
     }),
 
+    // Jumps to the 256MB region of the delay slot at the 26-bit target shifted left 2 bits, delayed by one instruction.
     &(Op::J, "j", OpC::J, &|p| {
-        p.ex.ol = p.ic.op.target();
+        p.ex.ol = (p.ic.pc.wrapping_add(4) & !0x0FFF_FFFF) | (p.ic.op.target() << 2);
     }),
 
+    // Jumps to the 256MB region of the delay slot at the 26-bit target shifted left 2 bits, delayed by one instruction.
+    // Stores the address of the instruction following the delay slot to register ra.
     &(Op::Jal, "jal", OpC::J, &|p| {
-        p.ex.ol = p.ic.op.target();
+        p.ex.ol = (p.ic.pc.wrapping_add(4) & !0x0FFF_FFFF) | (p.ic.op.target() << 2);
         p.ex.wlr = true;
     }),
 
@@ -122,9 +140,9 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
         p.ex.br = if p.rf.rt != p.rf.rs { true } else { false };
     }),
 
-    // Branches to the branch address if register p.rf.rs is less than 0.
+    // Branches to the branch address if register p.rf.rs is less than or equal to 0.
     &(Op::Blez, "blez", OpC::B, &|p| {
-        p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
+        p.ex.br = if (p.rf.rs as i64) <= 0 { true } else { false };
     }),
 
     // Branches to the branch address if register p.rf.rs is greater than 0.
@@ -137,13 +155,16 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
     // Sign-extends the 16-bit immediate and adds it to register p.rf.rs. Stores the 32-bit result to register p.rf.rt (sign-extends the result in the 64-bit mode).
     // Generates an exception if a 2's complement integer overflow occup.rf.rs.
     [&(Op::Addi, "addi", OpC::I, &|p| {
-        p.ex.ol = (p.rf.rs as i64 + p.ic.op.imm() as i16 as i64) as u64
+        match (p.rf.rs as i32).checked_add(p.ic.op.imm() as i16 as i32) {
+            Some(r) => p.ex.ol = r as i64 as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
     // Sign-extends the 16-bit immediate and adds it to register p.rf.rs. Stores the 32-bit result to register p.rf.rt (sign-extends the result in the 64-bit mode).
     // Does not generate an exception even if an integer overflow occup.rf.rs.
     &(Op::Addiu, "addiu", OpC::I, &|p| {
-        p.ex.ol = (p.rf.rs as i64 + p.ic.op.imm() as i16 as i64) as u64
+        p.ex.ol = (p.rf.rs as i32).wrapping_add(p.ic.op.imm() as i16 as i32) as i64 as u64
     }),
 
     // Sign-extends the 16-bit immediate and compares it with register p.rf.rs as a signed integer. If p.rf.rs is less than the immediate, stores 1 to register p.rf.rt; otherwise, stores 0 to register p.rf.rt.
@@ -187,9 +208,8 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
         unimplemented!()
     }),
 
-    &(Op::Cop2, "cop2", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // The VR4300 has no coprocessor 2; this raises a coprocessor unusable or reserved instruction exception in VR4300::ex.
+    &(Op::Cop2, "cop2", OpC::C, &|_| {}),
 
     &RESERVED,
 
@@ -203,31 +223,47 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
         p.ex.br = if p.rf.rt != p.rf.rs { true } else { false };
     }),
 
-    // Branches to the branch address if register p.rf.rs is less than 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
+    // Branches to the branch address if register p.rf.rs is less than or equal to 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
     &(Op::Blezl, "blezl", OpC::B, &|p| {
-        p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
+        p.ex.br = if (p.rf.rs as i64) <= 0 { true } else { false };
     }),
 
+    // Branches to the branch address if register p.rf.rs is greater than 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
     &(Op::Bgtzl, "bgtzl", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) > 0 { true } else { false };
     })],
 
     /* ROW: 3 */
 
+    // Adds the 16-bit sign-extended immediate to register rs and stores the 64-bit result to register rt.
+    // Generates an exception if a 2's complement integer overflow occurs.
     [&(Op::Daddi, "daddi", OpC::I, &|p| {
-        unimplemented!()
+        match (p.rf.rs as i64).checked_add(p.ic.op.imm() as i16 as i64) {
+            Some(r) => p.ex.ol = r as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
+    // Adds the 16-bit sign-extended immediate to register rs and stores the 64-bit result to register rt.
+    // Does not generate an exception even if an integer overflow occurs.
     &(Op::Daddiu, "daddiu", OpC::I, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs.wrapping_add(p.ic.op.imm() as i16 as i64 as u64)
     }),
 
+    // Generates an address by adding a sign-extended offset to the contents of register base.
+    // Shifts a doubleword specified by the address to the left, so that a byte specified by the address is at the leftmost position of the doubleword.
+    // Merges the result of the shift and the contents of register rt, and loads the result to register rt.
     &(Op::Ldl, "ldl", OpC::L, &|p| {
-        unimplemented!()
+        let s = (p.addr() & 7) * 8;
+        p.ex.ol = (p.dc.dc << s) | (p.rf.rt & !(u64::MAX << s))
     }),
 
+    // Generates an address by adding a sign-extended offset to the contents of register base.
+    // Shifts a doubleword specified by the address to the right, so that a byte specified by the address is at the rightmost position of the doubleword.
+    // Merges the result of the shift and the contents of register rt, and loads the result to register rt.
     &(Op::Ldr, "ldr", OpC::L, &|p| {
-        unimplemented!()
+        let s = (7 - (p.addr() & 7)) * 8;
+        p.ex.ol = (p.dc.dc >> s) | (p.rf.rt & !(u64::MAX >> s))
     }),
 
     &RESERVED,
@@ -252,7 +288,9 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
     // Generates an address by adding a sign-extended offset to the contents of register base.
     // Shifts a word specified by the address to the left, so that a byte specified by the address is at the leftmost position of the word. Sign-extends (in the 64bit mode), merges the result of the shift and the contents of register rt, and loads the result to register rt.
     &(Op::Lwl, "lwl", OpC::L, &|p| {
-        unimplemented!()
+        let s = (p.addr() & 3) * 8;
+        let w = ((p.dc.dc as u32) << s) | (p.rf.rt as u32 & !(0xFFFF_FFFFu32 << s));
+        p.ex.ol = w as i32 as i64 as u64
     }),
 
     // Generates an address by adding a sign-extended offset to the contents of register base.
@@ -277,7 +315,10 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
     // Shifts a word specified by the address to the right, so that a byte specified by the address is at the rightmost position of the word.
     // Sign-extends (in the 64bit mode), merges the result of the shift and the contents of register rt, and loads the result to register rt.
     &(Op::Lwr, "lwr", OpC::L, &|p| {
-        unimplemented!()
+        let s = (3 - (p.addr() & 3)) * 8;
+        let w = ((p.dc.dc as u32) >> s) | (p.rf.rt as u32 & !(0xFFFF_FFFFu32 >> s));
+        /* the word is only sign-extended once its most significant byte has been loaded */
+        p.ex.ol = if s == 0 { w as i32 as i64 as u64 } else { (p.rf.rt & 0xFFFF_FFFF_0000_0000) | w as u64 }
     }),
 
     // Generates an address by adding a sign-extended offset to the contents of register base.
@@ -304,7 +345,9 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
     // Shifts the contents of register rt to the right so that the leftmost byte of the word is at the position of the byte specified by the address.
     // Stores the result of the shift to the lower portion of the word in memory.
     &(Op::Swl, "swl", OpC::S, &|p| {
-        unimplemented!()
+        let s = (p.addr() & 3) * 8;
+        let m = 0xFFFF_FFFFu32 >> s;
+        p.ex.ol = ((p.dc.dc as u32 & !m) | ((p.rf.rt as u32) >> s)) as u64
     }),
 
     // Generates an address by adding a sign-extended offset to the contents of register base.
@@ -313,84 +356,106 @@ pub const OP_TABLE: [[&OpTup; 8]; 8] = [
         p.ex.ol = p.rf.rt as u32 as u64
     }),
 
+    // Generates an address by adding a sign-extended offset to the contents of register base.
+    // Shifts the contents of register rt to the right so that the leftmost byte of the doubleword is at the position of the byte specified by the address.
+    // Stores the result of the shift to the lower portion of the doubleword in memory.
     &(Op::Sdl, "sdl", OpC::S, &|p| {
-        unimplemented!()
+        let s = (p.addr() & 7) * 8;
+        let m = u64::MAX >> s;
+        p.ex.ol = (p.dc.dc & !m) | (p.rf.rt >> s)
     }),
 
+    // Generates an address by adding a sign-extended offset to the contents of register base.
+    // Shifts the contents of register rt to the left so that the rightmost byte of the doubleword is at the position of the byte specified by the address.
+    // Stores the result of the shift to the higher portion of the doubleword in memory.
     &(Op::Sdr, "sdr", OpC::S, &|p| {
-        unimplemented!()
+        let s = (7 - (p.addr() & 7)) * 8;
+        let m = u64::MAX << s;
+        p.ex.ol = (p.dc.dc & !m) | (p.rf.rt << s)
     }),
 
+    // Generates an address by adding a sign-extended offset to the contents of register base.
+    // Shifts the contents of register rt to the left so that the rightmost byte of the word is at the position of the byte specified by the address.
+    // Stores the result of the shift to the higher portion of the word in memory.
     &(Op::Swr, "swr", OpC::S, &|p| {
-        unimplemented!()
+        let s = (3 - (p.addr() & 3)) * 8;
+        let m = 0xFFFF_FFFFu32 << s;
+        p.ex.ol = ((p.dc.dc as u32 & !m) | ((p.rf.rt as u32) << s)) as u64
     }),
 
-    &(Op::Cache, "cache", OpC::I, &|p| {
-        unimplemented!()
-    })],
+    // Performs the cache operation op on the line selected by the address generated from base and offset; carried out in VR4300::dc.
+    &(Op::Cache, "cache", OpC::I, &|_| {})],
 
     /* ROW: 6 */
 
+    // Loads the word at the address generated from base and offset to register rt and starts an atomic read-modify-write sequence.
     [&(Op::Ll, "ll", OpC::L, &|p| {
-        unimplemented!()
+        p.ex.ol = p.dc.dc as i32 as i64 as u64
     }),
 
+    // Loads the word at the address generated from base and offset to floating-point register ft.
     &(Op::Lwc1, "lwc1", OpC::L, &|p| {
-        unimplemented!()
+        p.ex.ol = p.dc.dc as u32 as u64
     }),
 
-    &(Op::Lwc2, "lwc2", OpC::L, &|p| {
-        unimplemented!()
-    }),
+    // The VR4300 has no coprocessor 2; this raises a coprocessor unusable or reserved instruction exception in VR4300::ex.
+    &(Op::Lwc2, "lwc2", OpC::L, &|_| {}),
 
     &RESERVED,
 
+    // Loads the doubleword at the address generated from base and offset to register rt and starts an atomic read-modify-write sequence.
     &(Op::Lld, "lld", OpC::L, &|p| {
-        unimplemented!()
+        p.ex.ol = p.dc.dc
     }),
 
+    // Loads the doubleword at the address generated from base and offset to floating-point register ft.
     &(Op::Ldc1, "ldc1", OpC::L, &|p| {
-        unimplemented!()
+        p.ex.ol = p.dc.dc
     }),
 
-    &(Op::Ldc2, "ldc2", OpC::L, &|p| {
-        unimplemented!()
-    }),
+    // The VR4300 has no coprocessor 2; this raises a coprocessor unusable or reserved instruction exception in VR4300::ex.
+    &(Op::Ldc2, "ldc2", OpC::L, &|_| {}),
 
+    // Loads the doubleword at the address generated from base and offset to register rt.
     &(Op::Ld, "ld", OpC::L, &|p| {
-        unimplemented!()
+        p.ex.ol = p.dc.dc
     })],
 
     /* ROW: 7 */
 
+    // Stores the low-order word of register rt to the address generated from base and offset if the sequence started by ll was not broken.
+    // Register rt is set to 1 if the store took place and 0 otherwise.
     [&(Op::Sc, "sc", OpC::S, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt as u32 as u64
     }),
 
+    // Stores the low word of floating-point register ft to the address generated from base and offset.
     &(Op::Swc1, "swc1", OpC::S, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt as u32 as u64
     }),
 
-    &(Op::Swc2, "swc2", OpC::S, &|p| {
-        unimplemented!()
-    }),
+    // The VR4300 has no coprocessor 2; this raises a coprocessor unusable or reserved instruction exception in VR4300::ex.
+    &(Op::Swc2, "swc2", OpC::S, &|_| {}),
 
     &RESERVED,
 
+    // Stores the contents of register rt to the address generated from base and offset if the sequence started by lld was not broken.
+    // Register rt is set to 1 if the store took place and 0 otherwise.
     &(Op::Scd, "scd", OpC::S, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt
     }),
 
+    // Stores the contents of floating-point register ft to the address generated from base and offset.
     &(Op::Sdc1, "sdc1", OpC::S, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt
     }),
 
-    &(Op::Sdc2, "sdc2", OpC::S, &|p| {
-        unimplemented!()
-    }),
+    // The VR4300 has no coprocessor 2; this raises a coprocessor unusable or reserved instruction exception in VR4300::ex.
+    &(Op::Sdc2, "sdc2", OpC::S, &|_| {}),
 
+    // Stores the contents of register rt to the address generated from base and offset.
     &(Op::Sd, "sd", OpC::S, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt
     })],
 ];
 
@@ -448,12 +513,14 @@ pub const SP_OP_TABLE: [[&OpTup; 8]; 8] = [
     &RESERVED,
     &RESERVED,
 
+    // Generates a system call exception.
     &(Op::Syscall, "syscall", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.exc = Some(Exc::Sys)
     }),
 
+    // Generates a breakpoint exception.
     &(Op::Brk, "brk", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.exc = Some(Exc::Bp)
     }),
 
     &RESERVED,
@@ -464,70 +531,133 @@ pub const SP_OP_TABLE: [[&OpTup; 8]; 8] = [
 
     /* ROW: 2 */
 
+    // Transfers the contents of special register HI to register rd.
     [&(Op::Mfhi, "mfhi", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.hi
     }),
 
+    // Transfers the contents of register rs to special register HI.
     &(Op::Mthi, "mthi", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs
     }),
 
+    // Transfers the contents of special register LO to register rd.
     &(Op::Mflo, "mflo", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.lo
     }),
 
+    // Transfers the contents of register rs to special register LO.
     &(Op::Mtlo, "mtlo", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs
     }),
 
+    // Shifts the contents of register rt to the left by the number of bits in the low-order 6 bits of register rs, and stores the result to register rd.
     &(Op::Dsllv, "dsllv", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt << (p.rf.rs & 0x3F)
     }),
 
-    &(Op::Reserved, "resered", OpC::R, &|p| {
-        unimplemented!()
-    }),
+    &RESERVED,
 
+    // Shifts the contents of register rt to the right by the number of bits in the low-order 6 bits of register rs, inserting 0 to the high-order bits.
     &(Op::Dsrlv, "dsrlv", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt >> (p.rf.rs & 0x3F)
     }),
 
+    // Shifts the contents of register rt to the right by the number of bits in the low-order 6 bits of register rs, sign-extending the high-order bits.
     &(Op::Dsrav, "dsrav", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = ((p.rf.rt as i64) >> (p.rf.rs & 0x3F)) as u64
     })],
 
     /* ROW: 3 */
 
+    /* Multiplies and divides write HI through p.ex.oh and LO through p.ex.ol. */
+
+    // Multiplies the 32-bit signed contents of registers rs and rt.
+    // Stores the sign-extended low-order word of the result to LO and the high-order word to HI.
     [&(Op::Mult, "mult", OpC::R, &|p| {
-        unimplemented!()
+        let r = (p.rf.rs as i32 as i64) * (p.rf.rt as i32 as i64);
+        p.ex.ol = r as i32 as i64 as u64;
+        p.ex.oh = (r >> 32) as i32 as i64 as u64;
     }),
 
+    // Multiplies the 32-bit unsigned contents of registers rs and rt.
+    // Stores the sign-extended low-order word of the result to LO and the high-order word to HI.
     &(Op::Multu, "multu", OpC::R, &|p| {
-        unimplemented!()
+        let r = (p.rf.rs as u32 as u64) * (p.rf.rt as u32 as u64);
+        p.ex.ol = r as i32 as i64 as u64;
+        p.ex.oh = (r >> 32) as i32 as i64 as u64;
     }),
 
+    // Divides the 32-bit signed contents of register rs by rt.
+    // Stores the sign-extended quotient to LO and remainder to HI. Dividing by zero leaves -1 or 1 in LO and rs in HI.
     &(Op::Div, "div", OpC::R, &|p| {
-        unimplemented!()
-    }),
-
+        let (n, d) = (p.rf.rs as i32, p.rf.rt as i32);
+        if d == 0 {
+            p.ex.ol = (if n >= 0 { -1i64 } else { 1 }) as u64;
+            p.ex.oh = n as i64 as u64;
+        } else {
+            p.ex.ol = n.wrapping_div(d) as i64 as u64;
+            p.ex.oh = n.wrapping_rem(d) as i64 as u64;
+        }
+    }),
+
+    // Divides the 32-bit unsigned contents of register rs by rt.
+    // Stores the sign-extended quotient to LO and remainder to HI. Dividing by zero leaves all ones in LO and rs in HI.
     &(Op::Divu, "divu", OpC::R, &|p| {
-        unimplemented!()
-    }),
-
+        let (n, d) = (p.rf.rs as u32, p.rf.rt as u32);
+        match n.checked_div(d) {
+            Some(q) => {
+                p.ex.ol = q as i32 as i64 as u64;
+                p.ex.oh = (n % d) as i32 as i64 as u64;
+            }, None => {
+                p.ex.ol = u64::MAX;
+                p.ex.oh = n as i32 as i64 as u64;
+            }
+        }
+    }),
+
+    // Multiplies the 64-bit signed contents of registers rs and rt.
+    // Stores the low-order doubleword of the result to LO and the high-order doubleword to HI.
     &(Op::Dmult, "dmult", OpC::R, &|p| {
-        unimplemented!()
+        let r = (p.rf.rs as i64 as i128) * (p.rf.rt as i64 as i128);
+        p.ex.ol = r as u64;
+        p.ex.oh = (r >> 64) as u64;
     }),
 
+    // Multiplies the 64-bit unsigned contents of registers rs and rt.
+    // Stores the low-order doubleword of the result to LO and the high-order doubleword to HI.
     &(Op::Dmultu, "dmultu", OpC::R, &|p| {
-        unimplemented!()
+        let r = (p.rf.rs as u128) * (p.rf.rt as u128);
+        p.ex.ol = r as u64;
+        p.ex.oh = (r >> 64) as u64;
     }),
 
+    // Divides the 64-bit signed contents of register rs by rt.
+    // Stores the quotient to LO and remainder to HI. Dividing by zero leaves -1 or 1 in LO and rs in HI.
     &(Op::Ddiv, "ddiv", OpC::R, &|p| {
-        unimplemented!()
-    }),
-
-    &(Op::Ddivu    , "ddivu", OpC::R, &|p| {
-        unimplemented!()
+        let (n, d) = (p.rf.rs as i64, p.rf.rt as i64);
+        if d == 0 {
+            p.ex.ol = (if n >= 0 { -1i64 } else { 1 }) as u64;
+            p.ex.oh = n as u64;
+        } else {
+            p.ex.ol = n.wrapping_div(d) as u64;
+            p.ex.oh = n.wrapping_rem(d) as u64;
+        }
+    }),
+
+    // Divides the 64-bit unsigned contents of register rs by rt.
+    // Stores the quotient to LO and remainder to HI. Dividing by zero leaves all ones in LO and rs in HI.
+    &(Op::Ddivu, "ddivu", OpC::R, &|p| {
+        let (n, d) = (p.rf.rs, p.rf.rt);
+        match n.checked_div(d) {
+            Some(q) => {
+                p.ex.ol = q;
+                p.ex.oh = n % d;
+            }, None => {
+                p.ex.ol = u64::MAX;
+                p.ex.oh = n;
+            }
+        }
     })],
 
     /* ROW: 4 */
@@ -535,23 +665,31 @@ pub const SP_OP_TABLE: [[&OpTup; 8]; 8] = [
     // Adds the contents of register rs and rt, and stores (sign-extends in the 64-bit mode) the 32-bit result to register rd.
     // Generates an exception if an integer overflow occurs.
     [&(Op::Add, "add", OpC::R, &|p| {
-        p.ex.ol = ((p.rf.rs as u32) + (p.rf.rt as u32)) as u64
+        match (p.rf.rs as i32).checked_add(p.rf.rt as i32) {
+            Some(r) => p.ex.ol = r as i64 as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
     // Adds the contents of register rs and rt, and stores (sign-extends in the 64-bit mode) the 32-bit result to register rd.
     // Does not generate an exception even if an integer overflow occurs.
     &(Op::Addu, "addu", OpC::R, &|p| {
-        p.ex.ol = ((p.rf.rs as u32) + (p.rf.rt as u32)) as u64
+        p.ex.ol = (p.rf.rs as i32).wrapping_add(p.rf.rt as i32) as i64 as u64
     }),
 
-    // Subtracts the contents of register rs from register rt, and stores (sign-extends in the 64-bit mode) the result to register rd.
+    // Subtracts the contents of register rt from register rs, and stores (sign-extends in the 64-bit mode) the result to register rd.
     // Generates an exception if an integer overflow occurs.
     &(Op::Sub, "sub", OpC::R, &|p| {
-        p.ex.ol = ((p.rf.rt as u32) - (p.rf.rs as u32)) as u64
+        match (p.rf.rs as i32).checked_sub(p.rf.rt as i32) {
+            Some(r) => p.ex.ol = r as i64 as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
+    // Subtracts the contents of register rt from register rs, and stores (sign-extends in the 64-bit mode) the result to register rd.
+    // Does not generate an exception even if an integer overflow occurs.
     &(Op::Subu, "subu", OpC::R, &|p| {
-        p.ex.ol = ((p.rf.rt as u32) - (p.rf.rs as u32)) as u64
+        p.ex.ol = (p.rf.rs as i32).wrapping_sub(p.rf.rt as i32) as i64 as u64
     }),
 
     // ANDs the contents of registers rs and rt in bit units, and stores the result to register rd.
@@ -588,80 +726,106 @@ pub const SP_OP_TABLE: [[&OpTup; 8]; 8] = [
         unimplemented!()
     }),
 
+    // Adds the contents of registers rs and rt, and stores the 64-bit result to register rd.
+    // Generates an exception if an integer overflow occurs.
     &(Op::Dadd, "dadd", OpC::R, &|p| {
-        unimplemented!()
+        match (p.rf.rs as i64).checked_add(p.rf.rt as i64) {
+            Some(r) => p.ex.ol = r as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
+    // Adds the contents of registers rs and rt, and stores the 64-bit result to register rd.
+    // Does not generate an exception even if an integer overflow occurs.
     &(Op::Daddu, "daddu", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs.wrapping_add(p.rf.rt)
     }),
 
+    // Subtracts the contents of register rt from register rs, and stores the 64-bit result to register rd.
+    // Generates an exception if an integer overflow occurs.
     &(Op::Dsub, "dsub", OpC::R, &|p| {
-        unimplemented!()
+        match (p.rf.rs as i64).checked_sub(p.rf.rt as i64) {
+            Some(r) => p.ex.ol = r as u64,
+            None => p.ex.exc = Some(Exc::Ov)
+        }
     }),
 
+    // Subtracts the contents of register rt from register rs, and stores the 64-bit result to register rd.
+    // Does not generate an exception even if an integer overflow occurs.
     &(Op::Dsubu, "dsubu", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs.wrapping_sub(p.rf.rt)
     })],
 
     /* ROW: 6 */
 
+    // Compares the contents of registers rs and rt as signed integers. Generates a trap exception if rs is greater than or equal to rt.
     [&(Op::Tge, "tge", OpC::R, &|p| {
-        unimplemented!()
+        if (p.rf.rs as i64) >= (p.rf.rt as i64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of registers rs and rt as unsigned integers. Generates a trap exception if rs is greater than or equal to rt.
     &(Op::Tgeu, "tgeu", OpC::R, &|p| {
-        unimplemented!()
+        if p.rf.rs >= p.rf.rt { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of registers rs and rt as signed integers. Generates a trap exception if rs is less than rt.
     &(Op::Tlt, "tlt", OpC::R, &|p| {
-        unimplemented!()
+        if (p.rf.rs as i64) < (p.rf.rt as i64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of registers rs and rt as unsigned integers. Generates a trap exception if rs is less than rt.
     &(Op::Tltu, "tltu", OpC::R, &|p| {
-        unimplemented!()
+        if p.rf.rs < p.rf.rt { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Generates a trap exception if the contents of registers rs and rt are equal.
     &(Op::Teq, "teq", OpC::R, &|p| {
-        unimplemented!()
+        if p.rf.rs == p.rf.rt { p.ex.exc = Some(Exc::Tr) }
     }),
 
     &RESERVED,
 
+    // Generates a trap exception if the contents of registers rs and rt are not equal.
     &(Op::Tne, "tne", OpC::R, &|p| {
-        unimplemented!()
+        if p.rf.rs != p.rf.rt { p.ex.exc = Some(Exc::Tr) }
     }),
 
     &RESERVED],
 
     /* ROW: 7 */
 
+    // Shifts the contents of register rt sa bits to the left, inserts 0 to the low-order bits, and stores the result to register rd.
     [&(Op::Dsll, "dsll", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt << p.ic.op.sa()
     }),
 
     &RESERVED,
 
+    // Shifts the contents of register rt sa bits to the right, inserts 0 to the high-order bits, and stores the result to register rd.
     &(Op::Dsrl, "dsrl", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt >> p.ic.op.sa()
     }),
 
+    // Shifts the contents of register rt sa bits to the right, sign-extends the high-order bits, and stores the result to register rd.
     &(Op::Dsra, "dsra", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = ((p.rf.rt as i64) >> p.ic.op.sa()) as u64
     }),
 
+    // Shifts the contents of register rt 32+sa bits to the left, inserts 0 to the low-order bits, and stores the result to register rd.
     &(Op::Dsll32, "dsll32", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt << (32 + p.ic.op.sa())
     }),
 
     &RESERVED,
 
+    // Shifts the contents of register rt 32+sa bits to the right, inserts 0 to the high-order bits, and stores the result to register rd.
     &(Op::Dsrl32, "dsrl32", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt >> (32 + p.ic.op.sa())
     }),
 
+    // Shifts the contents of register rt 32+sa bits to the right, sign-extends the high-order bits, and stores the result to register rd.
     &(Op::Dsra32, "dsra32", OpC::R, &|p| {
-        unimplemented!()
+        p.ex.ol = ((p.rf.rt as i64) >> (32 + p.ic.op.sa())) as u64
     })],
 ];
 
@@ -670,10 +834,12 @@ pub const RI_OP_TABLE: [[&OpTup; 8]; 4] = [
 
     /* ROW: 0 */
 
+    // Branches to the branch address if register p.rf.rs is less than 0.
     [&(Op::Bltz, "bltz", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
     }),
 
+    // Branches to the branch address if register p.rf.rs is greater than or equal to 0.
     &(Op::Bgez, "bgez", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) >= 0 { true } else { false };
     }),
@@ -683,9 +849,9 @@ pub const RI_OP_TABLE: [[&OpTup; 8]; 4] = [
         p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
     }),
 
-    // Branches to the branch address if register p.rf.rs is greater than 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
+    // Branches to the branch address if register p.rf.rs is greater than or equal to 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
     &(Op::Bgezl, "bgezl", OpC::B, &|p| {
-        p.ex.br = if (p.rf.rs as i64) > 0 { true } else { false };
+        p.ex.br = if (p.rf.rs as i64) >= 0 { true } else { false };
     }),
 
     &RESERVED,
@@ -695,55 +861,68 @@ pub const RI_OP_TABLE: [[&OpTup; 8]; 4] = [
 
     /* ROW: 1 */
 
+    // Compares the contents of register rs with the sign-extended immediate as signed integers. Generates a trap exception if rs is greater than or equal to it.
     [&(Op::Tgei, "tgei", OpC::I, &|p| {
-        unimplemented!()
+        if (p.rf.rs as i64) >= (p.ic.op.imm() as i16 as i64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of register rs with the sign-extended immediate as unsigned integers. Generates a trap exception if rs is greater than or equal to it.
     &(Op::Tgeiu, "tgeiu", OpC::I, &|p| {
-        unimplemented!()
+        if p.rf.rs >= (p.ic.op.imm() as i16 as i64 as u64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of register rs with the sign-extended immediate as signed integers. Generates a trap exception if rs is less than it.
     &(Op::Tlti, "tlti", OpC::I, &|p| {
-        unimplemented!()
+        if (p.rf.rs as i64) < (p.ic.op.imm() as i16 as i64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Compares the contents of register rs with the sign-extended immediate as unsigned integers. Generates a trap exception if rs is less than it.
     &(Op::Tltiu, "tltiu", OpC::I, &|p| {
-        unimplemented!()
+        if p.rf.rs < (p.ic.op.imm() as i16 as i64 as u64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
+    // Generates a trap exception if the contents of register rs equal the sign-extended immediate.
     &(Op::Teqi, "teqi", OpC::I, &|p| {
-        unimplemented!()
+        if p.rf.rs == (p.ic.op.imm() as i16 as i64 as u64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
     &RESERVED,
 
+    // Generates a trap exception if the contents of register rs do not equal the sign-extended immediate.
     &(Op::Tnei, "tnei", OpC::I, &|p| {
-        unimplemented!()
+        if p.rf.rs != (p.ic.op.imm() as i16 as i64 as u64) { p.ex.exc = Some(Exc::Tr) }
     }),
 
     &RESERVED],
 
     /* ROW: 2 */
 
+    // Branches to the branch address if register p.rf.rs is less than 0.
+    // Stores the address of the instruction following the delay slot to register ra.
     [&(Op::Bltzal, "bltzal", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
         p.ex.wlr = true;
     }),
 
+    // Branches to the branch address if register p.rf.rs is greater than or equal to 0.
+    // Stores the address of the instruction following the delay slot to register ra.
     &(Op::Bgezal, "bgezal", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) >= 0 { true } else { false };
         p.ex.wlr = true;
     }),
 
+    // Branches to the branch address if register p.rf.rs is less than 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
+    // Stores the address of the instruction following the delay slot to register ra.
     &(Op::Bltzall, "bltzall", OpC::B, &|p| {
         p.ex.br = if (p.rf.rs as i64) < 0 { true } else { false };
         p.ex.wlr = true;
-        unimplemented!()
     }),
 
+    // Branches to the branch address if register p.rf.rs is greater than or equal to 0. If the branch condition is not satisfied, the instruction in the branch delay slot is discarded.
+    // Stores the address of the instruction following the delay slot to register ra.
     &(Op::Bgezall, "bgezall", OpC::B, &|p| {
+        p.ex.br = if (p.rf.rs as i64) >= 0 { true } else { false };
         p.ex.wlr = true;
-        unimplemented!()
     }),
 
     &RESERVED,
@@ -769,33 +948,37 @@ pub const COP_OP_RS_TABLE: [[&OpTup; 8]; 4] = [
     /* ROW: 0 */
 
     // Loads the contents of the word of the general purpose register rd of CP0 to the general purpose register rt of the CPU.
+    // The word is sign-extended to 64 bits.
     [&(Op::Mf, "mf", OpC::C, &|p| {
-        p.ex.ol = p.rf.rs
+        p.ex.ol = p.rf.rs as i32 as i64 as u64
     }),
 
     // Loads the contents of the doubleword of the general purpose register rd of CP0 to the general purpose register rt of the CPU.
     &(Op::Dmf, "dmf", OpC::C, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs
     }),
 
+    // Loads the contents of control register rd of the coprocessor to the general purpose register rt of the CPU.
     &(Op::Cf, "cf", OpC::C, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rs
     }),
 
     &RESERVED,
 
     // Loads the contents of the word of the general purpose register rt of the CPU to the general purpose register rd of CP0.
+    // The word is sign-extended to 64 bits.
     &(Op::Mt, "mt", OpC::C, &|p| {
-        p.ex.ol = p.rf.rt
+        p.ex.ol = p.rf.rt as i32 as i64 as u64
     }),
 
     // Loads the contents of the doubleword of the general purpose register rt of the CPU to the general purpose register rd of CP0.
     &(Op::Dmt, "dmt", OpC::C, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt
     }),
 
+    // Loads the contents of the general purpose register rt of the CPU to control register rd of the coprocessor.
     &(Op::Ct, "ct", OpC::C, &|p| {
-        unimplemented!()
+        p.ex.ol = p.rf.rt
     }),
 
     &RESERVED],
@@ -845,21 +1028,19 @@ pub const COP_OP_RT_TABLE: [[&OpTup; 8]; 4] = [
 
     /* ROW: 0 */
 
-    [&(Op::Bcf, "bcf", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    /* The coprocessor condition is tested by VR4300::ex. */
 
-    &(Op::Bct, "bct", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Branches to the branch address if the coprocessor condition is false.
+    [&(Op::Bcf, "bcf", OpC::C, &|_| {}),
 
-    &(Op::Bcfl, "bcfl", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Branches to the branch address if the coprocessor condition is true.
+    &(Op::Bct, "bct", OpC::C, &|_| {}),
 
-    &(Op::Bctl, "bctl", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Branches to the branch address if the coprocessor condition is false, nullifying the delay slot if not taken.
+    &(Op::Bcfl, "bcfl", OpC::C, &|_| {}),
+
+    // Branches to the branch address if the coprocessor condition is true, nullifying the delay slot if not taken.
+    &(Op::Bctl, "bctl", OpC::C, &|_| {}),
 
     &RESERVED,
     &RESERVED,
@@ -907,29 +1088,25 @@ pub const COP_OP_FN_TABLE: [[&OpTup; 8]; 4] = [
 
     [&RESERVED,
 
-    &(Op::Tlbr, "tlbr", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Loads the contents of the TLB entry specified by the Index register to EntryHi, EntryLo0, EntryLo1 and PageMask.
+    &(Op::Tlbr, "tlbr", OpC::C, &|_| {}),
 
-    &(Op::Tlbwi, "tlbwi", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Stores the contents of EntryHi, EntryLo0, EntryLo1 and PageMask to the TLB entry specified by the Index register.
+    &(Op::Tlbwi, "tlbwi", OpC::C, &|_| {}),
 
     &RESERVED,
     &RESERVED,
     &RESERVED,
 
-    &(Op::Tlbwr, "tlbwr", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Stores the contents of EntryHi, EntryLo0, EntryLo1 and PageMask to the TLB entry specified by the Random register.
+    &(Op::Tlbwr, "tlbwr", OpC::C, &|_| {}),
 
     &RESERVED],
 
     /* ROW: 1 */
 
-    [&(Op::Tlbp, "tlbp", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Searches the TLB for an entry matching EntryHi and stores its index to the Index register (sets the P bit if none matches).
+    [&(Op::Tlbp, "tlbp", OpC::C, &|_| {}),
 
     &RESERVED,
     &RESERVED,
@@ -952,9 +1129,8 @@ pub const COP_OP_FN_TABLE: [[&OpTup; 8]; 4] = [
 
     /* ROW: 3 */
 
-    [&(Op::Eret, "eret", OpC::C, &|p| {
-        unimplemented!()
-    }),
+    // Returns from an exception, clearing the ERL (or EXL) bit of Status and jumping to ErrorEPC (or EPC).
+    [&(Op::Eret, "eret", OpC::C, &|_| {}),
 
     &RESERVED,
     &RESERVED,
@@ -964,3 +1140,165 @@ pub const COP_OP_FN_TABLE: [[&OpTup; 8]; 4] = [
     &RESERVED,
     &RESERVED],
 ];
+
+/* A constant 2-d array of the CP1 opcodes selected by the funct field when fmt is S, D, W or L. */
+/* The arithmetic is carried out by CP1::exec. */
+pub const CP1_OP_FN_TABLE: [[&OpTup; 8]; 8] = [
+
+    /* ROW: 0 */
+
+    // Adds the contents of floating-point registers fs and ft in the specified format and stores the result to fd.
+    [&(Op::Fadd, "add", OpC::C, &|_| {}),
+
+    // Subtracts the contents of floating-point register ft from fs in the specified format and stores the result to fd.
+    &(Op::Fsub, "sub", OpC::C, &|_| {}),
+
+    // Multiplies the contents of floating-point registers fs and ft in the specified format and stores the result to fd.
+    &(Op::Fmul, "mul", OpC::C, &|_| {}),
+
+    // Divides the contents of floating-point register fs by ft in the specified format and stores the result to fd.
+    &(Op::Fdiv, "div", OpC::C, &|_| {}),
+
+    // Calculates the positive square root of floating-point register fs in the specified format and stores the result to fd.
+    &(Op::Fsqrt, "sqrt", OpC::C, &|_| {}),
+
+    // Calculates the absolute value of floating-point register fs in the specified format and stores the result to fd.
+    &(Op::Fabs, "abs", OpC::C, &|_| {}),
+
+    // Copies the contents of floating-point register fs to fd in the specified format.
+    &(Op::Fmov, "mov", OpC::C, &|_| {}),
+
+    // Inverts the sign of the contents of floating-point register fs in the specified format and stores the result to fd.
+    &(Op::Fneg, "neg", OpC::C, &|_| {})],
+
+    /* ROW: 1 */
+
+    // Rounds the contents of floating-point register fs to the nearest 64-bit fixed-point value and stores the result to fd.
+    [&(Op::FroundL, "round.l", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward zero to a 64-bit fixed-point value and stores the result to fd.
+    &(Op::FtruncL, "trunc.l", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward positive infinity to a 64-bit fixed-point value and stores the result to fd.
+    &(Op::FceilL, "ceil.l", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward negative infinity to a 64-bit fixed-point value and stores the result to fd.
+    &(Op::FfloorL, "floor.l", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs to the nearest 32-bit fixed-point value and stores the result to fd.
+    &(Op::FroundW, "round.w", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward zero to a 32-bit fixed-point value and stores the result to fd.
+    &(Op::FtruncW, "trunc.w", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward positive infinity to a 32-bit fixed-point value and stores the result to fd.
+    &(Op::FceilW, "ceil.w", OpC::C, &|_| {}),
+
+    // Rounds the contents of floating-point register fs toward negative infinity to a 32-bit fixed-point value and stores the result to fd.
+    &(Op::FfloorW, "floor.w", OpC::C, &|_| {})],
+
+    /* ROW: 2 */
+
+    [&CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED],
+
+    /* ROW: 3 */
+
+    [&CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED],
+
+    /* ROW: 4 */
+
+    // Converts the contents of floating-point register fs to single precision using the current rounding mode and stores the result to fd.
+    [&(Op::Fcvts, "cvt.s", OpC::C, &|_| {}),
+
+    // Converts the contents of floating-point register fs to double precision and stores the result to fd.
+    &(Op::Fcvtd, "cvt.d", OpC::C, &|_| {}),
+
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+
+    // Converts the contents of floating-point register fs to a 32-bit fixed-point value using the current rounding mode and stores the result to fd.
+    &(Op::Fcvtw, "cvt.w", OpC::C, &|_| {}),
+
+    // Converts the contents of floating-point register fs to a 64-bit fixed-point value using the current rounding mode and stores the result to fd.
+    &(Op::Fcvtl, "cvt.l", OpC::C, &|_| {}),
+
+    &CP1_RESERVED,
+    &CP1_RESERVED],
+
+    /* ROW: 5 */
+
+    [&CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED,
+    &CP1_RESERVED],
+
+    /* ROW: 6 */
+
+    // Sets the condition bit if fs and ft compare false.
+    [&(Op::Fcf, "c.f", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare unordered.
+    &(Op::Fcun, "c.un", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare equal.
+    &(Op::Fceq, "c.eq", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare unordered or equal.
+    &(Op::Fcueq, "c.ueq", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare ordered and less than.
+    &(Op::Fcolt, "c.olt", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare unordered or less than.
+    &(Op::Fcult, "c.ult", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare ordered and less than or equal.
+    &(Op::Fcole, "c.ole", OpC::C, &|_| {}),
+
+    // Sets the condition bit if fs and ft compare unordered or less than or equal.
+    &(Op::Fcule, "c.ule", OpC::C, &|_| {})],
+
+    /* ROW: 7 */
+
+    // Sets the condition bit like c.f, raising an invalid operation if fs or ft is a NaN.
+    [&(Op::Fcsf, "c.sf", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.un, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcngle, "c.ngle", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.eq, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcseq, "c.seq", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.ueq, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcngl, "c.ngl", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.olt, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fclt, "c.lt", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.ult, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcnge, "c.nge", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.ole, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcle, "c.le", OpC::C, &|_| {}),
+
+    // Sets the condition bit like c.ule, raising an invalid operation if fs or ft is a NaN.
+    &(Op::Fcngt, "c.ngt", OpC::C, &|_| {})],
+];
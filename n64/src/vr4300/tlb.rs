@@ -0,0 +1,130 @@
+/* tlb.rs - The VR4300's 32-entry joint translation lookaside buffer. */
+
+/*
+
+TLB entry (as seen through the CP0 registers)
+
+  PageMask:  [24:13] MASK - page size mask (4KB - 16MB)
+  EntryHi:   [63:62] R - region, [39:13] VPN2 - virtual page number / 2, [7:0] ASID
+  EntryLo0:  [25:6] PFN - page frame number of the even page, [5:3] C, [2] D, [1] V, [0] G
+  EntryLo1:  [25:6] PFN - page frame number of the odd page, [5:3] C, [2] D, [1] V, [0] G
+
+*/
+
+/* Number of entries in the joint TLB. */
+pub const TLB_SIZE: usize = 32;
+
+/* Writable bits of the TLB related CP0 registers. */
+pub const PAGEMASK_MASK: u64 = 0x0000_0000_01FF_E000;
+pub const ENTRYHI_MASK: u64 = 0xC000_00FF_FFFF_E0FF;
+pub const ENTRYLO_MASK: u64 = 0x0000_0000_3FFF_FFFF;
+
+/* Bits of EntryHi compared against a virtual address. */
+pub const ENTRYHI_VPN2: u64 = 0xC000_00FF_FFFF_E000;
+const ENTRYHI_ASID: u64 = 0x0000_0000_0000_00FF;
+
+/* EntryLo flags. */
+pub const ENTRYLO_G: u64 = 1 << 0;
+pub const ENTRYLO_V: u64 = 1 << 1;
+pub const ENTRYLO_D: u64 = 1 << 2;
+const ENTRYLO_C_SHIFT: u64 = 3;
+
+/* The ways in which a mapped address can fail to translate. */
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TlbFault {
+    /* No entry matches the virtual address. */
+    Miss,
+    /* The matching entry's page is not valid. */
+    Invalid,
+    /* The matching entry's page is not dirty (writable) on a store. */
+    Modified
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TlbEntry {
+    pub mask: u64,
+    pub hi: u64,
+    pub lo0: u64,
+    pub lo1: u64,
+    /* the global bit is the AND of the G bits of both EntryLo registers */
+    pub g: bool
+}
+
+impl TlbEntry {
+
+    pub fn new(mask: u64, hi: u64, lo0: u64, lo1: u64) -> TlbEntry {
+        TlbEntry {
+            mask: mask & PAGEMASK_MASK,
+            hi: hi & ENTRYHI_MASK,
+            lo0: lo0 & ENTRYLO_MASK & !ENTRYLO_G,
+            lo1: lo1 & ENTRYLO_MASK & !ENTRYLO_G,
+            g: (lo0 & lo1 & ENTRYLO_G) != 0
+        }
+    }
+
+    /* Returns the bits of a virtual address that select the even/odd page pair. */
+    fn vpn2_mask(&self) -> u64 {
+        ENTRYHI_VPN2 & !self.mask
+    }
+
+    /* Determines whether the entry maps the virtual address in the given address space. */
+    fn matches(&self, vaddr: u64, asid: u8) -> bool {
+        (vaddr & self.vpn2_mask()) == (self.hi & self.vpn2_mask()) &&
+            (self.g || (self.hi & ENTRYHI_ASID) as u8 == asid)
+    }
+}
+
+pub struct Tlb {
+    pub entries: [TlbEntry; TLB_SIZE]
+}
+
+impl Tlb {
+
+    pub fn new() -> Tlb {
+        Tlb {
+            entries: [TlbEntry::new(0, 0, 0, 0); TLB_SIZE]
+        }
+    }
+
+    /* Reads the entry at the given index. */
+    pub fn read(&self, index: usize) -> TlbEntry {
+        self.entries[index % TLB_SIZE]
+    }
+
+    /* Writes the entry at the given index. */
+    pub fn write(&mut self, index: usize, entry: TlbEntry) {
+        self.entries[index % TLB_SIZE] = entry;
+    }
+
+    /* Searches for an entry matching the VPN2 and ASID of an EntryHi value. */
+    pub fn probe(&self, hi: u64) -> Option<usize> {
+        self.entries.iter().position(|e| e.matches(hi, (hi & ENTRYHI_ASID) as u8))
+    }
+
+    /* Translates a mapped virtual address to a physical address. */
+    pub fn translate(&self, vaddr: u64, asid: u8, write: bool) -> Result<(u32, u8), TlbFault> {
+
+        let e = match self.entries.iter().find(|e| e.matches(vaddr, asid)) {
+            Some(e) => e,
+            None => return Err(TlbFault::Miss)
+        };
+
+        /* the page offset mask is half of the page pair mask */
+        let offset_mask = (e.mask | 0x1FFF) >> 1;
+
+        /* the bit above the page offset selects the even or odd page */
+        let lo = if (vaddr & (offset_mask + 1)) != 0 { e.lo1 } else { e.lo0 };
+
+        if (lo & ENTRYLO_V) == 0 {
+            return Err(TlbFault::Invalid);
+        }
+
+        if write && (lo & ENTRYLO_D) == 0 {
+            return Err(TlbFault::Modified);
+        }
+
+        let pfn = ((lo >> 6) & 0xF_FFFF) << 12;
+
+        Ok((((pfn & !offset_mask) | (vaddr & offset_mask)) as u32, ((lo >> ENTRYLO_C_SHIFT) & 0x7) as u8))
+    }
+}
@@ -13,6 +13,8 @@ use std::str;
 extern crate n64;
 use n64::N64;
 use n64::N64_ROM_HEADER_SIZE;
+use n64::Reset;
+use n64::Ram;
 
 #[repr(C, packed)]
 pub struct N64_ROM_HEADER {
@@ -37,7 +39,7 @@ fn main() {
     let args: Vec<_> = env::args().collect();
     /* Ensure the proper arguments were provided, otherwise print usage information. */
     if args.len() < 3 {
-        println!("r64: Another Nintendo 64 emulator; this time, written in Rust.\n\nOriginally authored by George Morgan. (george@george-morgan.com)\n\nusage: r64 [rom]");
+        println!("r64: Another Nintendo 64 emulator; this time, written in Rust.\n\nOriginally authored by George Morgan. (george@george-morgan.com)\n\nusage: r64 [rom] [pifrom] [--expansion-pak]");
         return;
     }
 
@@ -70,8 +72,11 @@ fn main() {
     let name = str::from_utf8(&header.name).unwrap().trim();
     println!("The ROM is {:?}.", name);
 
+    /* Fit the Expansion Pak if requested. */
+    let ram = if args.iter().skip(3).any(|a| a == "--expansion-pak") { Ram::Expansion } else { Ram::Base };
+
     /* Create the N64. */
-    let mut n64 = N64::new(crom, prom);
+    let mut n64 = N64::new(crom, prom, ram);
 
     let mut rl = Editor::<()>::new();
     'main_loop: loop {
@@ -83,6 +88,12 @@ fn main() {
                     // /* Prints the CPU state. */
                     "print" | "p" => {
                         println!("{:?}", n64.cpu);
+                    }, "reset" | "r" => {
+                        /* Presses the reset button. */
+                        n64.reset(Reset::Soft);
+                    }, "cold" => {
+                        /* Power cycles the console. */
+                        n64.reset(Reset::Cold);
                     }, "quit" | "q" => {
                         break 'main_loop;
                     }, "go" | "g" => {
@@ -90,7 +101,7 @@ fn main() {
                             n64.step();
                             //println!("{:?}", n64.cpu);
                         }
-                    }, "step" | "s" | _ => {
+                    }, _ => {
                         n64.step();
                     },
                 }